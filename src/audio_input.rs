@@ -0,0 +1,228 @@
+use std::ffi::CString;
+use std::os::raw::c_void;
+use std::sync::{Arc, Mutex};
+use obs_wrapper::{
+    obs_sys::{
+        audio_data, obs_source_t, obs_weak_source_t, obs_get_audio, obs_get_source_by_name,
+        obs_source_get_weak_source, obs_source_release, obs_weak_source_get_source,
+        obs_weak_source_release, obs_source_add_audio_capture_callback,
+        obs_source_remove_audio_capture_callback, audio_output_get_channels, AUDIO_OUTPUT_FRAMES,
+    },
+    audio::*,
+};
+
+/// Silence passed to callbacks in place of the samples of muted sources.
+static SILENCE: [f32; AUDIO_OUTPUT_FRAMES as usize] = [0.0; AUDIO_OUTPUT_FRAMES as usize];
+
+/// The number of channels of the current speaker layout, e.g. 6 for 5.1 surround.
+pub fn get_audio_channel_count() -> usize {
+    unsafe { audio_output_get_channels(obs_get_audio()) as usize }
+}
+
+/// The origin of the audio samples to analyze.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum AudioInputDescriptor {
+    /// An output mix/track, zero-based.
+    Mix(usize),
+    /// The audio of a single source, identified by its name.
+    Source(String),
+}
+
+impl AudioInputDescriptor {
+    /// Uses the source, if its name is non-empty, otherwise the mix.
+    pub fn new(mix: usize, source_name: &str) -> Self {
+        if source_name.is_empty() {
+            AudioInputDescriptor::Mix(mix)
+        } else {
+            AudioInputDescriptor::Source(source_name.to_string())
+        }
+    }
+}
+
+/// The planar samples of a single audio callback.
+pub enum AudioSamples<'a> {
+    Mix(AudioData<'a, ()>),
+    Source {
+        data: &'a audio_data,
+        channels: usize,
+        muted: bool,
+    },
+}
+
+impl<'a> AudioSamples<'a> {
+    pub fn samples_normalized(&self, channel: usize) -> Option<&[f32]> {
+        match self {
+            AudioSamples::Mix(audio_data) => {
+                // Planes of channels missing from the speaker layout must not be accessed.
+                if channel >= get_audio_channel_count() {
+                    return None;
+                }
+
+                audio_data.samples_normalized(channel)
+            },
+            AudioSamples::Source { data, channels, muted } => {
+                if channel >= *channels || data.data[channel].is_null() {
+                    return None;
+                }
+
+                let frames = data.frames as usize;
+
+                if *muted {
+                    return Some(&SILENCE[..frames.min(SILENCE.len())]);
+                }
+
+                Some(unsafe {
+                    std::slice::from_raw_parts(data.data[channel] as *const f32, frames)
+                })
+            },
+        }
+    }
+}
+
+pub type AudioInputCallback = Arc<dyn Fn(&AudioSamples) + Send + Sync>;
+
+unsafe extern "C" fn audio_capture_callback(
+    param: *mut c_void,
+    _source: *mut obs_source_t,
+    data: *const audio_data,
+    muted: bool,
+) {
+    let callback = &*(param as *const AudioInputCallback);
+
+    if data.is_null() {
+        return;
+    }
+
+    (callback)(&AudioSamples::Source {
+        data: &*data,
+        channels: get_audio_channel_count(),
+        muted,
+    });
+}
+
+/// An audio capture callback registered on a source.
+/// The source is referenced weakly, so that it can be removed while being captured.
+struct AudioCapture {
+    weak: *mut obs_weak_source_t,
+    param: *mut AudioInputCallback,
+}
+
+// The callback is only accessed by OBS between registration and removal.
+unsafe impl Send for AudioCapture {}
+unsafe impl Sync for AudioCapture {}
+
+impl AudioCapture {
+    fn by_name(name: &str, callback: AudioInputCallback) -> Option<Self> {
+        let name = CString::new(name).ok()?;
+
+        unsafe {
+            let source = obs_get_source_by_name(name.as_ptr());
+
+            if source.is_null() {
+                return None;
+            }
+
+            let param = Box::into_raw(Box::new(callback));
+
+            obs_source_add_audio_capture_callback(source, Some(audio_capture_callback), param as *mut c_void);
+
+            let weak = obs_source_get_weak_source(source);
+
+            obs_source_release(source);
+
+            Some(Self { weak, param })
+        }
+    }
+
+    fn exists(&self) -> bool {
+        unsafe {
+            let source = obs_weak_source_get_source(self.weak);
+
+            if source.is_null() {
+                return false;
+            }
+
+            obs_source_release(source);
+
+            true
+        }
+    }
+}
+
+impl Drop for AudioCapture {
+    fn drop(&mut self) {
+        unsafe {
+            let source = obs_weak_source_get_source(self.weak);
+
+            // Callbacks of removed sources are no longer called.
+            if !source.is_null() {
+                obs_source_remove_audio_capture_callback(source, Some(audio_capture_callback), self.param as *mut c_void);
+                obs_source_release(source);
+            }
+
+            obs_weak_source_release(self.weak);
+            drop(Box::from_raw(self.param));
+        }
+    }
+}
+
+enum AudioInputConnection {
+    Mix(AudioOutput),
+    Source(AudioCapture),
+}
+
+/// Calls the callback with the samples of the audio input.
+/// Sources may be created after the filter, or removed and created again, which is
+/// why the connection to a source is re-established, whenever `connect` is called.
+pub struct AudioInput {
+    descriptor: AudioInputDescriptor,
+    callback: AudioInputCallback,
+    connection: Mutex<Option<AudioInputConnection>>,
+}
+
+impl AudioInput {
+    pub fn new(descriptor: AudioInputDescriptor, callback: AudioInputCallback) -> Self {
+        let result = Self {
+            descriptor,
+            callback,
+            connection: Mutex::new(None),
+        };
+
+        result.connect();
+
+        result
+    }
+
+    /// Connects to the audio input, unless already connected.
+    pub fn connect(&self) {
+        let mut connection = self.connection.lock().unwrap();
+
+        if let Some(AudioInputConnection::Source(capture)) = connection.as_ref() {
+            if !capture.exists() {
+                *connection = None;
+            }
+        }
+
+        if connection.is_some() {
+            return;
+        }
+
+        *connection = match &self.descriptor {
+            AudioInputDescriptor::Mix(mix) => {
+                let callback = self.callback.clone();
+                let audio_output = Audio::get().connect_output(
+                    *mix,
+                    Box::new(move |audio_data| {
+                        (callback)(&AudioSamples::Mix(audio_data));
+                    }),
+                );
+
+                Some(AudioInputConnection::Mix(audio_output))
+            },
+            AudioInputDescriptor::Source(source_name) => {
+                AudioCapture::by_name(source_name, self.callback.clone())
+                    .map(AudioInputConnection::Source)
+            },
+        };
+    }
+}
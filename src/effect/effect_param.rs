@@ -1,6 +1,8 @@
 use std::sync::Arc;
 use std::borrow::Cow;
-use obs_wrapper::{obs_sys::MAX_AUDIO_MIXES, context::*, graphics::*, source::*};
+use std::ffi::CString;
+use std::path::{Path, PathBuf};
+use obs_wrapper::{obs_sys::{MAX_AUDIO_MIXES, MAX_AUDIO_CHANNELS, gs_texture_t, obs_source_t}, context::*, graphics::*, source::*};
 use smallvec::{SmallVec, smallvec};
 use paste::item;
 use crate::*;
@@ -193,6 +195,42 @@ impl<T: EffectParamType> EffectParam<T> {
     }
 }
 
+/// A texture param bound to a texture not managed by `EffectParam`, such as a render target.
+pub struct EffectParamRawTexture {
+    pub param: GraphicsContextDependentDisabled<GraphicsEffectParamTyped<ShaderParamTypeTexture>>,
+    pub texture: *mut gs_texture_t,
+}
+
+impl EffectParamRawTexture {
+    pub fn new(param: GraphicsContextDependentDisabled<GraphicsEffectParamTyped<ShaderParamTypeTexture>>) -> Self {
+        Self {
+            param,
+            texture: std::ptr::null_mut(),
+        }
+    }
+
+    /// Sets the texture to be assigned, or null to leave the param unbound.
+    /// The texture must remain valid until it is assigned.
+    pub fn set_texture(&mut self, texture: *mut gs_texture_t) {
+        self.texture = texture;
+    }
+
+    pub fn assign_value<'a>(&mut self, context: &'a FilterContext) {
+        set_param_texture(&mut self.param.as_enabled_mut(context.graphics()), self.texture);
+    }
+
+    pub fn enable_and_drop(self, graphics_context: &GraphicsContext) {
+        self.param.enable(graphics_context);
+    }
+}
+
+/// The name of a uniform displayed in the hotkey settings.
+fn get_hotkey_description<S: PropertyDescriptorSpecialization>(identifier: &str, descriptor: Option<PropertyDescriptor<S>>) -> String {
+    descriptor
+        .map(|descriptor| descriptor.description.to_string_lossy().into_owned())
+        .unwrap_or_else(|| identifier.to_string())
+}
+
 // A helper trait to ensure most custom effect params follow the same structure.
 // Not all custom effect params implement this trait, however.
 pub trait EffectParamCustom: BindableProperty + Sized {
@@ -209,7 +247,16 @@ pub trait EffectParamCustom: BindableProperty + Sized {
 
 pub struct EffectParamCustomBool {
     pub effect_param: EffectParamBool,
-    pub property: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorBool>
+    pub property: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorBool>,
+    pub hotkeys: Option<ParamHotkeys>,
+    /// Whether the `Hold` hotkey is held, which inverts the value without changing the setting.
+    pub held: bool,
+}
+
+impl EffectParamCustomBool {
+    fn prepare_value(&mut self) {
+        self.effect_param.prepare_value(self.property.get_value() != self.held);
+    }
 }
 
 impl EffectParamCustom for EffectParamCustomBool {
@@ -233,6 +280,12 @@ impl EffectParamCustom for EffectParamCustomBool {
             preprocess_result,
             settings,
         )?;
+        let hotkeys = ParamHotkeys::new(
+            identifier,
+            &get_hotkey_description(identifier, property.get_descriptor()),
+            &[HotkeyAction::Toggle, HotkeyAction::Hold],
+            preprocess_result,
+        )?;
         let mut effect_param = EffectParam::new(param.disable());
 
         effect_param.prepare_value(property.get_value());
@@ -240,6 +293,8 @@ impl EffectParamCustom for EffectParamCustomBool {
         Ok(Self {
             property,
             effect_param,
+            hotkeys,
+            held: false,
         })
     }
 }
@@ -251,7 +306,33 @@ impl BindableProperty for EffectParamCustomBool {
 
     fn reload_settings(&mut self, settings: &mut SettingsContext) {
         self.property.reload_settings(settings);
-        self.effect_param.prepare_value(self.property.get_value());
+        self.prepare_value();
+    }
+
+    fn hotkeys_mut(&mut self) -> Option<&mut ParamHotkeys> {
+        self.hotkeys.as_mut()
+    }
+
+    fn apply_hotkeys(&mut self, settings: &mut SettingsContext) {
+        let events = if let Some(hotkeys) = self.hotkeys.as_ref() {
+            hotkeys.take_events()
+        } else {
+            return;
+        };
+
+        for event in events {
+            match (event.action, event.pressed) {
+                (HotkeyAction::Toggle, true) => {
+                    let value = !self.property.get_value();
+
+                    self.property.set_value(value, settings);
+                },
+                (HotkeyAction::Hold, pressed) => self.held = pressed,
+                _ => continue,
+            }
+
+            self.prepare_value();
+        }
     }
 
     fn prepare_values(&mut self) {}
@@ -269,9 +350,99 @@ impl BindableProperty for EffectParamCustomBool {
     }
 }
 
+/// The property of an integer uniform, displayed as a drop-down list, if `<NAME>__options` are specified.
+pub enum EffectParamCustomIntProperty {
+    Range(LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorI32>),
+    Options {
+        property: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorListI32>,
+        options: PropertyDescriptorSpecializationListI32,
+        default_value: i32,
+    },
+}
+
+impl EffectParamCustomIntProperty {
+    pub fn add_properties(&self, properties: &mut Properties) {
+        match self {
+            EffectParamCustomIntProperty::Range(property) => property.add_properties(properties),
+            EffectParamCustomIntProperty::Options { property, .. } => property.add_properties(properties),
+        }
+    }
+
+    pub fn reload_settings(&mut self, settings: &mut SettingsContext) {
+        match self {
+            EffectParamCustomIntProperty::Range(property) => property.reload_settings(settings),
+            EffectParamCustomIntProperty::Options { property, .. } => property.reload_settings(settings),
+        }
+    }
+
+    pub fn set_value(&mut self, value: i32, settings: &mut SettingsContext) {
+        match self {
+            EffectParamCustomIntProperty::Range(property) => property.set_value(value, settings),
+            EffectParamCustomIntProperty::Options { property, .. } => property.set_value(value, settings),
+        }
+    }
+
+    /// Returns the value following the current one, wrapping around the options, or clamped to the range.
+    pub fn get_next_value(&self, forward: bool) -> i32 {
+        let value = self.get_value();
+
+        match self {
+            EffectParamCustomIntProperty::Range(property) => {
+                let (min, max, step) = property.get_descriptor()
+                    .map(|descriptor| (descriptor.specialization.min, descriptor.specialization.max, descriptor.specialization.step.max(1)))
+                    .unwrap_or((std::i32::MIN, std::i32::MAX, 1));
+                let next_value = if forward {
+                    value.saturating_add(step)
+                } else {
+                    value.saturating_sub(step)
+                };
+
+                next_value.max(min).min(max)
+            },
+            EffectParamCustomIntProperty::Options { options, .. } => {
+                let count = options.items.len();
+                let index = options.items.iter()
+                    .position(|(_, item_value)| *item_value == value)
+                    .unwrap_or(0);
+                let next_index = if forward {
+                    (index + 1) % count
+                } else {
+                    (index + count - 1) % count
+                };
+
+                options.items[next_index].1
+            },
+        }
+    }
+
+    pub fn get_description(&self, identifier: &str) -> String {
+        match self {
+            EffectParamCustomIntProperty::Range(property) => get_hotkey_description(identifier, property.get_descriptor()),
+            EffectParamCustomIntProperty::Options { property, .. } => get_hotkey_description(identifier, property.get_descriptor()),
+        }
+    }
+
+    pub fn get_value(&self) -> i32 {
+        match self {
+            EffectParamCustomIntProperty::Range(property) => property.get_value(),
+            EffectParamCustomIntProperty::Options { property, options, default_value } => {
+                let value = property.get_value();
+
+                // Saved values may be missing from the list, if the options were changed since
+                if options.contains(value) {
+                    value
+                } else {
+                    *default_value
+                }
+            },
+        }
+    }
+}
+
 pub struct EffectParamCustomInt {
     pub effect_param: EffectParamInt,
-    pub property: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorI32>
+    pub property: EffectParamCustomIntProperty,
+    pub hotkeys: Option<ParamHotkeys>,
 }
 
 impl EffectParamCustom for EffectParamCustomInt {
@@ -284,21 +455,66 @@ impl EffectParamCustom for EffectParamCustomInt {
         settings: &mut SettingsContext,
         preprocess_result: &PreprocessResult,
     ) -> Result<Self, Cow<'static, str>> {
-        let property = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
-            LoadedValueTypePropertyArgs {
-                allow_definitions_in_source: true,
-                default_value: *param.get_param_value_default().unwrap_or(&0),
-                default_descriptor_specialization: Self::PropertyDescriptorSpecialization {
-                    min: std::i32::MIN,
-                    max: std::i32::MAX,
-                    step: 1,
-                    slider: false,
+        let options = preprocess_result.parse::<String>(&format!("{}__options", identifier))
+            .transpose()?;
+        let property = if let Some(options) = options {
+            let options = PropertyDescriptorSpecializationListI32::parse_options(&options)
+                .map_err(|err| format!("Invalid options of property `{}`: {}", identifier, err))?;
+
+            for hardcoded_identifier in &[identifier.to_string(), format!("{}__default", identifier)] {
+                if let Some(value) = preprocess_result.parse::<i32>(hardcoded_identifier).transpose()? {
+                    if !options.contains(value) {
+                        throw!(format!("The value `{}` of `{}` is not one of its options.", value, hardcoded_identifier));
+                    }
+                }
+            }
+
+            let default_value = param.get_param_value_default()
+                .copied()
+                .filter(|value| options.contains(*value))
+                .unwrap_or(options.items[0].1);
+            let property = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+                LoadedValueTypePropertyArgs {
+                    allow_definitions_in_source: true,
+                    default_value,
+                    default_descriptor_specialization: options.clone(),
                 },
-            },
+                identifier,
+                None,
+                preprocess_result,
+                settings,
+            )?;
+
+            EffectParamCustomIntProperty::Options {
+                default_value: preprocess_result.parse::<i32>(&format!("{}__default", identifier))
+                    .transpose()?
+                    .unwrap_or(default_value),
+                property,
+                options,
+            }
+        } else {
+            EffectParamCustomIntProperty::Range(<LoadedValueTypeProperty<_> as LoadedValueType>::from(
+                LoadedValueTypePropertyArgs {
+                    allow_definitions_in_source: true,
+                    default_value: *param.get_param_value_default().unwrap_or(&0),
+                    default_descriptor_specialization: Self::PropertyDescriptorSpecialization {
+                        min: std::i32::MIN,
+                        max: std::i32::MAX,
+                        step: 1,
+                        slider: false,
+                    },
+                },
+                identifier,
+                None,
+                preprocess_result,
+                settings,
+            )?)
+        };
+        let hotkeys = ParamHotkeys::new(
             identifier,
-            None,
+            &property.get_description(identifier),
+            &[HotkeyAction::Next, HotkeyAction::Previous],
             preprocess_result,
-            settings,
         )?;
         let mut effect_param = EffectParam::new(param.disable());
 
@@ -307,6 +523,7 @@ impl EffectParamCustom for EffectParamCustomInt {
         Ok(Self {
             property,
             effect_param,
+            hotkeys,
         })
     }
 }
@@ -321,6 +538,29 @@ impl BindableProperty for EffectParamCustomInt {
         self.effect_param.prepare_value(self.property.get_value());
     }
 
+    fn hotkeys_mut(&mut self) -> Option<&mut ParamHotkeys> {
+        self.hotkeys.as_mut()
+    }
+
+    fn apply_hotkeys(&mut self, settings: &mut SettingsContext) {
+        let events = if let Some(hotkeys) = self.hotkeys.as_ref() {
+            hotkeys.take_events()
+        } else {
+            return;
+        };
+
+        for event in events.into_iter().filter(|event| event.pressed) {
+            let new_value = match event.action {
+                HotkeyAction::Next => self.property.get_next_value(true),
+                HotkeyAction::Previous => self.property.get_next_value(false),
+                _ => continue,
+            };
+
+            self.property.set_value(new_value, settings);
+            self.effect_param.prepare_value(new_value);
+        }
+    }
+
     fn prepare_values(&mut self) {}
 
     fn stage_value<'a>(&mut self, graphics_context: &'a GraphicsContext) {
@@ -338,7 +578,8 @@ impl BindableProperty for EffectParamCustomInt {
 
 pub struct EffectParamCustomFloat {
     pub effect_param: EffectParamFloat,
-    pub property: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>
+    pub property: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
+    pub transition: Option<Transition>,
 }
 
 impl EffectParamCustom for EffectParamCustomFloat {
@@ -367,6 +608,7 @@ impl EffectParamCustom for EffectParamCustomFloat {
             preprocess_result,
             settings,
         )?;
+        let transition = Transition::from_identifier(identifier, vec![property.get_value()], preprocess_result)?;
         let mut effect_param = EffectParam::new(param.disable());
 
         effect_param.prepare_value(property.get_value() as f32);
@@ -374,6 +616,7 @@ impl EffectParamCustom for EffectParamCustomFloat {
         Ok(Self {
             property,
             effect_param,
+            transition,
         })
     }
 }
@@ -385,10 +628,19 @@ impl BindableProperty for EffectParamCustomFloat {
 
     fn reload_settings(&mut self, settings: &mut SettingsContext) {
         self.property.reload_settings(settings);
-        self.effect_param.prepare_value(self.property.get_value() as f32);
+
+        if let Some(transition) = self.transition.as_mut() {
+            transition.set_target(vec![self.property.get_value()]);
+        } else {
+            self.effect_param.prepare_value(self.property.get_value() as f32);
+        }
     }
 
-    fn prepare_values(&mut self) {}
+    fn prepare_values(&mut self) {
+        if let Some(value) = self.transition.as_mut().and_then(Transition::update) {
+            self.effect_param.prepare_value(value[0] as f32);
+        }
+    }
 
     fn stage_value<'a>(&mut self, graphics_context: &'a GraphicsContext) {
         self.effect_param.stage_value(graphics_context);
@@ -405,7 +657,9 @@ impl BindableProperty for EffectParamCustomFloat {
 
 pub struct EffectParamCustomColor {
     pub effect_param: EffectParamVec4,
-    pub property: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorColor>
+    pub property: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorColor>,
+    /// Interpolates in the Oklab color space, for perceptually uniform transitions.
+    pub transition: Option<Transition>,
 }
 
 impl EffectParamCustom for EffectParamCustomColor {
@@ -429,6 +683,11 @@ impl EffectParamCustom for EffectParamCustomColor {
             preprocess_result,
             settings,
         )?;
+        let transition = Transition::from_identifier(
+            identifier,
+            srgba_to_oklab((property.get_value() as Color).into()),
+            preprocess_result,
+        )?;
         let mut effect_param = EffectParam::new(param.disable());
 
         effect_param.prepare_value((property.get_value() as Color).into());
@@ -436,6 +695,7 @@ impl EffectParamCustom for EffectParamCustomColor {
         Ok(Self {
             property,
             effect_param,
+            transition,
         })
     }
 }
@@ -447,10 +707,19 @@ impl BindableProperty for EffectParamCustomColor {
 
     fn reload_settings(&mut self, settings: &mut SettingsContext) {
         self.property.reload_settings(settings);
-        self.effect_param.prepare_value((self.property.get_value() as Color).into());
+
+        if let Some(transition) = self.transition.as_mut() {
+            transition.set_target(srgba_to_oklab((self.property.get_value() as Color).into()));
+        } else {
+            self.effect_param.prepare_value((self.property.get_value() as Color).into());
+        }
     }
 
-    fn prepare_values(&mut self) {}
+    fn prepare_values(&mut self) {
+        if let Some(value) = self.transition.as_mut().and_then(Transition::update) {
+            self.effect_param.prepare_value(oklab_to_srgba(value));
+        }
+    }
 
     fn stage_value<'a>(&mut self, graphics_context: &'a GraphicsContext) {
         self.effect_param.stage_value(graphics_context);
@@ -465,171 +734,1419 @@ impl BindableProperty for EffectParamCustomColor {
     }
 }
 
-pub struct EffectParamCustomFFT {
+/// A texture loaded from an image file.
+/// Relative paths are resolved relative to the directory of the shader.
+pub struct EffectParamCustomTexture {
     pub effect_param: EffectParamTexture,
-    pub effect_param_previous: Option<EffectParamTexture>,
-    pub audio_fft: Option<Arc<GlobalStateAudioFFT>>,
-    pub property_mix: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorI32>,
-    pub property_channel: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorI32>,
-    pub property_dampening_factor_attack: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
-    pub property_dampening_factor_release: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
+    pub property: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorPath>,
+    pub shader_directory: PathBuf,
+    pub loaded_path: Option<PathBuf>,
+    /// The error which occurred while loading the texture at the current path.
+    pub load_error: Option<Cow<'static, str>>,
 }
 
-// Does not implement EffectParamCustom because of different argument requirements
-impl EffectParamCustomFFT {
-    pub fn new<'a>(
-        param: GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeTexture>>,
-        param_previous: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeTexture>>>,
+impl EffectParamCustomTexture {
+    fn load_texture(path: &Path) -> Result<TextureDescriptor, Cow<'static, str>> {
+        let image = image::open(path)
+            .map_err(|err| format!("Could not load the texture at path {:?}: {}", path, err))?
+            .to_rgba();
+        let (width, height) = image.dimensions();
+
+        Ok(TextureDescriptor {
+            dimensions: [width as usize, height as usize],
+            color_format: ColorFormatKind::RGBA,
+            levels: smallvec![image.into_raw()],
+            flags: 0,
+        })
+    }
+
+    /// Loads the texture, if the path was changed.
+    fn update_texture(&mut self) {
+        let path = self.property.get_value();
+        let path = if path.as_os_str().is_empty() {
+            None
+        } else {
+            Some(self.shader_directory.join(path))
+        };
+
+        self.load_error = None;
+
+        if path == self.loaded_path {
+            return;
+        }
+
+        let texture = if let Some(path) = path.as_ref() {
+            match Self::load_texture(path) {
+                Ok(texture) => texture,
+                Err(err) => {
+                    // Keep the path unloaded, so that loading is attempted again on next reload.
+                    self.load_error = Some(err);
+                    self.effect_param.prepare_value(Default::default());
+                    self.loaded_path = None;
+                    return;
+                },
+            }
+        } else {
+            Default::default()
+        };
+
+        self.effect_param.prepare_value(texture);
+        self.loaded_path = path;
+    }
+}
+
+impl EffectParamCustom for EffectParamCustomTexture {
+    type ShaderParamType = ShaderParamTypeTexture;
+    type PropertyDescriptorSpecialization = PropertyDescriptorSpecializationPath;
+
+    fn new<'a>(
+        param: GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<Self::ShaderParamType>>,
         identifier: &str,
         settings: &mut SettingsContext,
         preprocess_result: &PreprocessResult,
     ) -> Result<Self, Cow<'static, str>> {
-        let property_mix = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
-            LoadedValueTypePropertyArgs {
-                allow_definitions_in_source: false,
-                default_value: 1,
-                default_descriptor_specialization: PropertyDescriptorSpecializationI32 {
-                    min: 1,
-                    max: MAX_AUDIO_MIXES as i32,
-                    step: 1,
-                    slider: false,
-                },
-            },
-            identifier,
-            Some("mix"),
-            preprocess_result,
-            settings,
-        )?;
-        let property_channel = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
-            LoadedValueTypePropertyArgs {
-                allow_definitions_in_source: false,
-                default_value: 1,
-                default_descriptor_specialization: PropertyDescriptorSpecializationI32 {
-                    min: 1,
-                    max: 2, // FIXME: Causes crashes when `MAX_AUDIO_CHANNELS as i32` is used, supposedly fixed in next OBS release
-                    step: 1,
-                    slider: false,
-                },
-            },
-            identifier,
-            Some("channel"),
-            preprocess_result,
-            settings,
-        )?;
-        let property_dampening_factor_attack = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+        let shader_directory = preprocess_result.source_directory().to_path_buf();
+        let property = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
             LoadedValueTypePropertyArgs {
                 allow_definitions_in_source: true,
-                default_value: 0.0,
-                default_descriptor_specialization: PropertyDescriptorSpecializationF64 {
-                    min: 0.0,
-                    max: 100.0,
-                    step: 0.01,
-                    slider: true,
+                default_value: PathBuf::new(),
+                default_descriptor_specialization: Self::PropertyDescriptorSpecialization {
+                    path_type: PathType::File,
+                    filter: CString::new("Image Files (*.png *.jpg *.jpeg *.bmp *.tga) ;; All File Types | *.*").unwrap(),
+                    default_path: CString::new(shader_directory.to_string_lossy().as_ref())
+                        .unwrap_or_default(),
                 },
             },
             identifier,
-            Some("dampening_factor_attack"),
+            None,
             preprocess_result,
             settings,
         )?;
-        let property_dampening_factor_release = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+        let mut result = Self {
+            effect_param: EffectParam::new(param.disable()),
+            property,
+            shader_directory,
+            loaded_path: None,
+            load_error: None,
+        };
+
+        result.update_texture();
+
+        Ok(result)
+    }
+}
+
+impl BindableProperty for EffectParamCustomTexture {
+    fn add_properties(&self, properties: &mut Properties) {
+        self.property.add_properties(properties);
+    }
+
+    fn reload_settings(&mut self, settings: &mut SettingsContext) {
+        self.property.reload_settings(settings);
+        self.update_texture();
+    }
+
+    fn load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
+    }
+
+    fn prepare_values(&mut self) {}
+
+    fn stage_value<'a>(&mut self, graphics_context: &'a GraphicsContext) {
+        self.effect_param.stage_value(graphics_context);
+    }
+
+    fn assign_value<'a>(&mut self, graphics_context: &'a FilterContext) {
+        self.effect_param.assign_value(graphics_context);
+    }
+
+    fn enable_and_drop(self, graphics_context: &GraphicsContext) {
+        self.effect_param.enable_and_drop(graphics_context);
+    }
+}
+
+/// The video output of another source, rendered to a texture every frame.
+pub struct EffectParamCustomSource {
+    pub effect_param: EffectParamRawTexture,
+    pub property_source: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorSource>,
+    pub source_name: String,
+    pub source: Option<WeakSource>,
+    pub render_target: Option<RenderTarget>,
+    pub rendered: bool,
+}
+
+// Does not implement EffectParamCustom because of different argument requirements
+impl EffectParamCustomSource {
+    pub fn new<'a>(
+        param: GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeTexture>>,
+        identifier: &str,
+        filter: *mut obs_source_t,
+        settings: &mut SettingsContext,
+        preprocess_result: &PreprocessResult,
+    ) -> Result<Self, Cow<'static, str>> {
+        let property_source = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
             LoadedValueTypePropertyArgs {
                 allow_definitions_in_source: true,
-                default_value: 0.0,
-                default_descriptor_specialization: PropertyDescriptorSpecializationF64 {
-                    min: 0.0,
-                    max: 100.0,
-                    step: 0.01,
-                    slider: true,
-                },
+                default_value: String::new(),
+                default_descriptor_specialization: PropertyDescriptorSpecializationSource::video(filter),
             },
             identifier,
-            Some("dampening_factor_release"),
+            Some("source"),
             preprocess_result,
             settings,
         )?;
 
-        let audio_fft_descriptor = GlobalStateAudioFFTDescriptor::new(
-            property_mix.get_value() as usize - 1,
-            property_channel.get_value() as usize - 1,
-            property_dampening_factor_attack.get_value() / 100.0,
-            property_dampening_factor_release.get_value() / 100.0,
-            // TODO: Make customizable, but provide a sane default value
-            WindowFunction::Hanning,
-        );
-
         let mut result = Self {
-            effect_param: EffectParam::new(param.disable()),
-            effect_param_previous: param_previous.map(|param_previous| EffectParam::new(param_previous.disable())),
-            audio_fft: None,
-            property_mix,
-            property_channel,
-            property_dampening_factor_attack,
-            property_dampening_factor_release,
+            effect_param: EffectParamRawTexture::new(param.disable()),
+            property_source,
+            source_name: String::new(),
+            source: None,
+            render_target: None,
+            rendered: false,
         };
 
-        result.request_audio_fft();
+        result.request_source();
 
         Ok(result)
     }
 
-    fn request_audio_fft(&mut self) {
-        let audio_fft_descriptor = GlobalStateAudioFFTDescriptor::new(
-            self.property_mix.get_value() as usize - 1,
-            self.property_channel.get_value() as usize - 1,
-            self.property_dampening_factor_attack.get_value() / 100.0,
-            self.property_dampening_factor_release.get_value() / 100.0,
-            // TODO: Make customizable, but provide a sane default value
-            WindowFunction::Hanning,
-        );
+    /// Looks up the selected source, if it was changed or if it no longer exists.
+    /// Sources are referenced weakly, so that they can be removed while in use.
+    fn request_source(&mut self) {
+        let source_name = self.property_source.get_value();
+
+        if source_name == self.source_name {
+            if let Some(source) = self.source.as_ref() {
+                if source.exists() {
+                    return;
+                }
+            }
+        }
 
-        self.audio_fft = Some(GLOBAL_STATE.request_audio_fft(&audio_fft_descriptor));
+        self.source = WeakSource::by_name(&source_name);
+        self.source_name = source_name;
     }
 }
 
-impl BindableProperty for EffectParamCustomFFT {
+impl BindableProperty for EffectParamCustomSource {
     fn add_properties(&self, properties: &mut Properties) {
-        self.property_mix.add_properties(properties);
-        self.property_channel.add_properties(properties);
-        self.property_dampening_factor_attack.add_properties(properties);
-        self.property_dampening_factor_release.add_properties(properties);
+        self.property_source.add_properties(properties);
     }
 
     fn reload_settings(&mut self, settings: &mut SettingsContext) {
-        self.property_mix.reload_settings(settings);
-        self.property_channel.reload_settings(settings);
-        self.property_dampening_factor_attack.reload_settings(settings);
-        self.property_dampening_factor_release.reload_settings(settings);
-        self.request_audio_fft();
+        self.property_source.reload_settings(settings);
+        self.request_source();
     }
 
     fn prepare_values(&mut self) {
-        let fft_result = if let Some(result) = self.audio_fft.as_mut().unwrap().retrieve_result() {
-            result
+        // Reattach to a source that was removed and added again, or renamed back.
+        if self.source.is_none() || !self.source.as_ref().unwrap().exists() {
+            self.request_source();
+        }
+    }
+
+    fn stage_value<'a>(&mut self, _graphics_context: &'a GraphicsContext) {}
+
+    fn render_textures(&mut self, graphics_context: &GraphicsContext) {
+        let render_target = self.render_target.get_or_insert_with(|| RenderTarget::new(graphics_context));
+
+        if let Some(source) = self.source.as_ref() {
+            // Keep the previous texture, if rendering was skipped.
+            if let Some(rendered) = source.render(render_target, graphics_context) {
+                self.rendered = rendered;
+            }
         } else {
-            return;
-        };
-        let frequency_spectrum = &fft_result.frequency_spectrum;
+            self.rendered = false;
+        }
+
+        self.effect_param.set_texture(if self.rendered {
+            render_target.texture()
+        } else {
+            std::ptr::null_mut()
+        });
+    }
+
+    fn assign_value<'a>(&mut self, graphics_context: &'a FilterContext) {
+        self.effect_param.assign_value(graphics_context);
+    }
+
+    fn enable_and_drop(self, graphics_context: &GraphicsContext) {
+        self.effect_param.enable_and_drop(graphics_context);
+        if let Some(render_target) = self.render_target {
+            render_target.destroy(graphics_context);
+        }
+    }
+}
+
+/// The value type of a single component of a `VectorShaderParamType`.
+pub type VectorComponent<T> = <<<T as VectorShaderParamType>::Descriptor as LoadedValueTypePropertyDescriptor>::Specialization as ValuePropertyDescriptorSpecialization>::ValueType;
+
+/// A multi-component shader type, of which each component is displayed as a separate property.
+pub trait VectorShaderParamType: ShaderParamType + 'static {
+    type Descriptor: LoadedValueTypePropertyBounds;
+
+    /// The names of the components, used as suffixes of the property identifiers.
+    const COMPONENTS: &'static [&'static str];
+
+    fn default_specialization() -> <Self::Descriptor as LoadedValueTypePropertyDescriptor>::Specialization;
+    fn to_components(value: &Self::RustType) -> Vec<VectorComponent<Self>>;
+    fn from_components(components: &[VectorComponent<Self>]) -> Self::RustType;
+}
+
+fn default_vector_specialization_f64() -> PropertyDescriptorSpecializationF64 {
+    PropertyDescriptorSpecializationF64 {
+        min: std::f64::MIN,
+        max: std::f64::MAX,
+        step: 0.1,
+        slider: false,
+    }
+}
+
+fn default_vector_specialization_i32() -> PropertyDescriptorSpecializationI32 {
+    PropertyDescriptorSpecializationI32 {
+        min: std::i32::MIN,
+        max: std::i32::MAX,
+        step: 1,
+        slider: false,
+    }
+}
+
+impl VectorShaderParamType for ShaderParamTypeVec2 {
+    type Descriptor = LoadedValueTypePropertyDescriptorF64;
+
+    const COMPONENTS: &'static [&'static str] = &["x", "y"];
+
+    fn default_specialization() -> PropertyDescriptorSpecializationF64 {
+        default_vector_specialization_f64()
+    }
+
+    fn to_components(value: &[f32; 2]) -> Vec<f64> {
+        value.iter().map(|component| *component as f64).collect()
+    }
+
+    fn from_components(components: &[f64]) -> [f32; 2] {
+        [components[0] as f32, components[1] as f32]
+    }
+}
+
+impl VectorShaderParamType for ShaderParamTypeVec3 {
+    type Descriptor = LoadedValueTypePropertyDescriptorF64;
+
+    const COMPONENTS: &'static [&'static str] = &["x", "y", "z"];
+
+    fn default_specialization() -> PropertyDescriptorSpecializationF64 {
+        default_vector_specialization_f64()
+    }
+
+    fn to_components(value: &[f32; 3]) -> Vec<f64> {
+        value.iter().map(|component| *component as f64).collect()
+    }
+
+    fn from_components(components: &[f64]) -> [f32; 3] {
+        [components[0] as f32, components[1] as f32, components[2] as f32]
+    }
+}
+
+impl VectorShaderParamType for ShaderParamTypeIVec2 {
+    type Descriptor = LoadedValueTypePropertyDescriptorI32;
+
+    const COMPONENTS: &'static [&'static str] = &["x", "y"];
+
+    fn default_specialization() -> PropertyDescriptorSpecializationI32 {
+        default_vector_specialization_i32()
+    }
+
+    fn to_components(value: &[i32; 2]) -> Vec<i32> {
+        value.to_vec()
+    }
+
+    fn from_components(components: &[i32]) -> [i32; 2] {
+        [components[0], components[1]]
+    }
+}
+
+impl VectorShaderParamType for ShaderParamTypeIVec3 {
+    type Descriptor = LoadedValueTypePropertyDescriptorI32;
+
+    const COMPONENTS: &'static [&'static str] = &["x", "y", "z"];
+
+    fn default_specialization() -> PropertyDescriptorSpecializationI32 {
+        default_vector_specialization_i32()
+    }
+
+    fn to_components(value: &[i32; 3]) -> Vec<i32> {
+        value.to_vec()
+    }
+
+    fn from_components(components: &[i32]) -> [i32; 3] {
+        [components[0], components[1], components[2]]
+    }
+}
+
+impl VectorShaderParamType for ShaderParamTypeIVec4 {
+    type Descriptor = LoadedValueTypePropertyDescriptorI32;
+
+    const COMPONENTS: &'static [&'static str] = &["x", "y", "z", "w"];
+
+    fn default_specialization() -> PropertyDescriptorSpecializationI32 {
+        default_vector_specialization_i32()
+    }
+
+    fn to_components(value: &[i32; 4]) -> Vec<i32> {
+        value.to_vec()
+    }
+
+    fn from_components(components: &[i32]) -> [i32; 4] {
+        [components[0], components[1], components[2], components[3]]
+    }
+}
+
+impl VectorShaderParamType for ShaderParamTypeMat4 {
+    type Descriptor = LoadedValueTypePropertyDescriptorF64;
+
+    // `m<row><column>`
+    const COMPONENTS: &'static [&'static str] = &[
+        "m00", "m01", "m02", "m03",
+        "m10", "m11", "m12", "m13",
+        "m20", "m21", "m22", "m23",
+        "m30", "m31", "m32", "m33",
+    ];
+
+    fn default_specialization() -> PropertyDescriptorSpecializationF64 {
+        default_vector_specialization_f64()
+    }
+
+    fn to_components(value: &[[f32; 4]; 4]) -> Vec<f64> {
+        value.iter().flat_map(|row| row.iter()).map(|component| *component as f64).collect()
+    }
+
+    fn from_components(components: &[f64]) -> [[f32; 4]; 4] {
+        let mut value = [[0.0; 4]; 4];
+
+        for (index, component) in components.iter().enumerate() {
+            value[index / 4][index % 4] = *component as f32;
+        }
+
+        value
+    }
+}
+
+/// A multi-component uniform, with a separate property for each component.
+/// The properties are named `<identifier>__<component>`.
+pub struct EffectParamCustomVector<T>
+    where T: VectorShaderParamType,
+          <T as ShaderParamType>::RustType: Default,
+          VectorComponent<T>: TransitionComponent,
+{
+    pub effect_param: EffectParam<EffectParamTypeClone<T>>,
+    pub properties: Vec<LoadedValueTypeProperty<T::Descriptor>>,
+    pub transition: Option<Transition>,
+}
+
+pub type EffectParamCustomVec2 = EffectParamCustomVector<ShaderParamTypeVec2>;
+pub type EffectParamCustomVec3 = EffectParamCustomVector<ShaderParamTypeVec3>;
+pub type EffectParamCustomIVec2 = EffectParamCustomVector<ShaderParamTypeIVec2>;
+pub type EffectParamCustomIVec3 = EffectParamCustomVector<ShaderParamTypeIVec3>;
+pub type EffectParamCustomIVec4 = EffectParamCustomVector<ShaderParamTypeIVec4>;
+pub type EffectParamCustomMat4 = EffectParamCustomVector<ShaderParamTypeMat4>;
+
+impl<T> EffectParamCustomVector<T>
+    where T: VectorShaderParamType,
+          <T as ShaderParamType>::RustType: Default,
+          VectorComponent<T>: TransitionComponent,
+{
+    fn get_value(&self) -> <T as ShaderParamType>::RustType {
+        T::from_components(&self.get_components())
+    }
+
+    fn get_components(&self) -> Vec<VectorComponent<T>> {
+        self.properties.iter()
+            .map(|property| property.get_value())
+            .collect()
+    }
+
+    fn get_transition_components(&self) -> Vec<f64> {
+        self.get_components().iter()
+            .map(TransitionComponent::to_f64)
+            .collect()
+    }
+}
+
+impl<T> EffectParamCustom for EffectParamCustomVector<T>
+    where T: VectorShaderParamType,
+          <T as ShaderParamType>::RustType: Default,
+          VectorComponent<T>: TransitionComponent,
+{
+    type ShaderParamType = T;
+    type PropertyDescriptorSpecialization = <T::Descriptor as LoadedValueTypePropertyDescriptor>::Specialization;
+
+    fn new<'a>(
+        param: GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<Self::ShaderParamType>>,
+        identifier: &str,
+        settings: &mut SettingsContext,
+        preprocess_result: &PreprocessResult,
+    ) -> Result<Self, Cow<'static, str>> {
+        let default_components = param.get_param_value_default()
+            .map(T::to_components)
+            .unwrap_or_else(|| T::to_components(&Default::default()));
+        let properties = T::COMPONENTS.iter()
+            .zip(default_components)
+            .map(|(component, default_value)| {
+                <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+                    LoadedValueTypePropertyArgs {
+                        allow_definitions_in_source: true,
+                        default_value,
+                        default_descriptor_specialization: T::default_specialization(),
+                    },
+                    identifier,
+                    Some(*component),
+                    preprocess_result,
+                    settings,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut result = Self {
+            effect_param: EffectParam::new(param.disable()),
+            properties,
+            transition: None,
+        };
+
+        result.transition = Transition::from_identifier(identifier, result.get_transition_components(), preprocess_result)?;
+        result.effect_param.prepare_value(result.get_value());
+
+        Ok(result)
+    }
+}
+
+impl<T> BindableProperty for EffectParamCustomVector<T>
+    where T: VectorShaderParamType,
+          <T as ShaderParamType>::RustType: Default,
+          VectorComponent<T>: TransitionComponent,
+{
+    fn add_properties(&self, properties: &mut Properties) {
+        self.properties.iter().for_each(|property| property.add_properties(properties));
+    }
+
+    fn reload_settings(&mut self, settings: &mut SettingsContext) {
+        self.properties.iter_mut().for_each(|property| property.reload_settings(settings));
+
+        let components = self.get_transition_components();
+
+        if let Some(transition) = self.transition.as_mut() {
+            transition.set_target(components);
+        } else {
+            self.effect_param.prepare_value(self.get_value());
+        }
+    }
+
+    fn prepare_values(&mut self) {
+        if let Some(value) = self.transition.as_mut().and_then(Transition::update) {
+            let components = value.iter()
+                .map(|component| TransitionComponent::from_f64(*component))
+                .collect::<Vec<_>>();
+
+            self.effect_param.prepare_value(T::from_components(&components));
+        }
+    }
+
+    fn stage_value<'a>(&mut self, graphics_context: &'a GraphicsContext) {
+        self.effect_param.stage_value(graphics_context);
+    }
+
+    fn assign_value<'a>(&mut self, graphics_context: &'a FilterContext) {
+        self.effect_param.assign_value(graphics_context);
+    }
+
+    fn enable_and_drop(self, graphics_context: &GraphicsContext) {
+        self.effect_param.enable_and_drop(graphics_context);
+    }
+}
+
+/// The properties selecting the analyzed audio, `<NAME>__mix`, `<NAME>__source` and `<NAME>__channel`.
+pub struct AudioInputProperties {
+    pub property_mix: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorI32>,
+    pub property_source: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorSource>,
+    pub property_channel: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorI32>,
+}
+
+impl AudioInputProperties {
+    pub fn new(
+        identifier: &str,
+        settings: &mut SettingsContext,
+        preprocess_result: &PreprocessResult,
+    ) -> Result<Self, Cow<'static, str>> {
+        let property_mix = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: false,
+                default_value: 1,
+                default_descriptor_specialization: PropertyDescriptorSpecializationI32 {
+                    min: 1,
+                    max: MAX_AUDIO_MIXES as i32,
+                    step: 1,
+                    slider: false,
+                },
+            },
+            identifier,
+            Some("mix"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_source = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: String::new(),
+                default_descriptor_specialization: PropertyDescriptorSpecializationSource::audio(),
+            },
+            identifier,
+            Some("source"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_channel = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: false,
+                default_value: 1,
+                default_descriptor_specialization: PropertyDescriptorSpecializationI32 {
+                    min: 1,
+                    // Channels missing from the speaker layout are not analyzed
+                    max: MAX_AUDIO_CHANNELS as i32,
+                    step: 1,
+                    slider: false,
+                },
+            },
+            identifier,
+            Some("channel"),
+            preprocess_result,
+            settings,
+        )?;
+
+        Ok(Self {
+            property_mix,
+            property_source,
+            property_channel,
+        })
+    }
+
+    pub fn add_properties(&self, properties: &mut Properties) {
+        self.property_mix.add_properties(properties);
+        self.property_source.add_properties(properties);
+        self.property_channel.add_properties(properties);
+    }
+
+    pub fn reload_settings(&mut self, settings: &mut SettingsContext) {
+        self.property_mix.reload_settings(settings);
+        self.property_source.reload_settings(settings);
+        self.property_channel.reload_settings(settings);
+    }
+
+    /// The selected source, or the selected mix, if no source is selected.
+    pub fn descriptor(&self) -> AudioInputDescriptor {
+        AudioInputDescriptor::new(
+            self.property_mix.get_value() as usize - 1,
+            &self.property_source.get_value(),
+        )
+    }
+
+    /// The zero-based index of the selected channel.
+    pub fn channel(&self) -> usize {
+        self.property_channel.get_value() as usize - 1
+    }
+}
+
+/// The properties `<NAME>__dampening_factor_attack` and `<NAME>__dampening_factor_release`, in percent.
+pub struct DampeningProperties {
+    pub property_attack: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
+    pub property_release: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
+}
+
+impl DampeningProperties {
+    pub fn new(
+        identifier: &str,
+        settings: &mut SettingsContext,
+        preprocess_result: &PreprocessResult,
+    ) -> Result<Self, Cow<'static, str>> {
+        let mut load = |property_name: &str| {
+            <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+                LoadedValueTypePropertyArgs {
+                    allow_definitions_in_source: true,
+                    default_value: 0.0,
+                    default_descriptor_specialization: PropertyDescriptorSpecializationF64 {
+                        min: 0.0,
+                        max: 100.0,
+                        step: 0.01,
+                        slider: true,
+                    },
+                },
+                identifier,
+                Some(property_name),
+                preprocess_result,
+                settings,
+            )
+        };
+
+        Ok(Self {
+            property_attack: load("dampening_factor_attack")?,
+            property_release: load("dampening_factor_release")?,
+        })
+    }
+
+    pub fn add_properties(&self, properties: &mut Properties) {
+        self.property_attack.add_properties(properties);
+        self.property_release.add_properties(properties);
+    }
+
+    pub fn reload_settings(&mut self, settings: &mut SettingsContext) {
+        self.property_attack.reload_settings(settings);
+        self.property_release.reload_settings(settings);
+    }
+
+    /// The dampening factor of rising values, from 0 to 1.
+    pub fn attack(&self) -> f64 {
+        self.property_attack.get_value() / 100.0
+    }
+
+    /// The dampening factor of falling values, from 0 to 1.
+    pub fn release(&self) -> f64 {
+        self.property_release.get_value() / 100.0
+    }
+}
+
+/// A 2D texture of the most recent spectra, with the frequency bins along the X axis.
+/// Each row of the packed spectrum texture has its own band of `length` rows, stacked vertically.
+/// The rows of each band are written cyclically, the index of the most recently written row is assigned to `effect_param_row`.
+pub struct FFTSpectrogram {
+    pub effect_param: EffectParamTexture,
+    pub effect_param_row: Option<EffectParamInt>,
+    /// The batch number of the most recently written spectrum.
+    pub batch_number: Option<usize>,
+    /// The dimensions of the packed spectrum texture.
+    pub dimensions: [usize; 2],
+    /// The number of components per texel.
+    pub components: usize,
+    pub length: usize,
+    pub row: usize,
+    pub data: Vec<f32>,
+}
+
+impl FFTSpectrogram {
+    pub fn new(
+        effect_param: EffectParamTexture,
+        effect_param_row: Option<EffectParamInt>,
+    ) -> Self {
+        Self {
+            effect_param,
+            effect_param_row,
+            batch_number: None,
+            dimensions: [0, 0],
+            components: 1,
+            length: 0,
+            row: 0,
+            data: Vec::new(),
+        }
+    }
+
+    /// Writes each row of the packed texels into the next row of its band, if it was not written already.
+    pub fn push(&mut self, texels: &[f32], dimensions: [usize; 2], components: usize, batch_number: usize, length: usize) {
+        if self.batch_number == Some(batch_number) {
+            return;
+        }
+
+        let row_len = dimensions[0] * components;
+
+        // Clear the history, if the dimensions changed
+        if self.dimensions != dimensions || self.components != components || self.length != length {
+            self.dimensions = dimensions;
+            self.components = components;
+            self.length = length;
+            self.row = length - 1;
+            self.data = vec![0.0; row_len * dimensions[1] * self.length];
+        }
+
+        self.batch_number = Some(batch_number);
+        self.row = (self.row + 1) % self.length;
+
+        for (band, band_texels) in texels.chunks_exact(row_len).enumerate() {
+            let start = (band * self.length + self.row) * row_len;
+
+            self.data[start..start + row_len].copy_from_slice(band_texels);
+        }
+
+        // The whole texture is uploaded, as textures cannot be partially updated
+        // through the graphics wrapper. This only happens when a new batch is available.
+        let texture_data = unsafe {
+            std::slice::from_raw_parts::<u8>(
+                self.data.as_ptr() as *const _,
+                self.data.len() * std::mem::size_of::<f32>(),
+            )
+        }.to_vec();
+
+        self.effect_param.prepare_value(TextureDescriptor {
+            dimensions: [self.dimensions[0], self.dimensions[1] * self.length],
+            color_format: if self.components == 4 {
+                ColorFormatKind::RGBA32F
+            } else {
+                ColorFormatKind::R32F
+            },
+            levels: smallvec![texture_data],
+            flags: 0,
+        });
+
+        if let Some(effect_param_row) = self.effect_param_row.as_mut() {
+            effect_param_row.prepare_value(self.row as i32);
+        }
+    }
+
+    pub fn stage_value<'a>(&mut self, graphics_context: &'a GraphicsContext) {
+        self.effect_param.stage_value(graphics_context);
+
+        if let Some(effect_param_row) = self.effect_param_row.as_mut() {
+            effect_param_row.stage_value(graphics_context);
+        }
+    }
+
+    pub fn assign_value<'a>(&mut self, graphics_context: &'a FilterContext) {
+        self.effect_param.assign_value_if_staged(graphics_context);
+
+        if let Some(effect_param_row) = self.effect_param_row.as_mut() {
+            effect_param_row.assign_value_if_staged(graphics_context);
+        }
+    }
+
+    pub fn enable_and_drop(self, graphics_context: &GraphicsContext) {
+        self.effect_param.enable_and_drop(graphics_context);
+
+        if let Some(effect_param_row) = self.effect_param_row {
+            effect_param_row.enable_and_drop(graphics_context);
+        }
+    }
+}
+
+pub struct EffectParamCustomFFT {
+    pub effect_param: Option<EffectParamTexture>,
+    pub effect_param_previous: Option<EffectParamTexture>,
+    pub spectrogram: Option<FFTSpectrogram>,
+    /// One analyzer per channel, in the order they are packed in.
+    pub audio_ffts: Vec<Arc<GlobalStateAudioFFT>>,
+    pub audio_input: AudioInputProperties,
+    pub property_channel_layout: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorListString>,
+    pub dampening: DampeningProperties,
+    pub property_window: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorListString>,
+    pub property_window_cosine_coefficients: Vec<LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>>,
+    pub property_fft_size: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorListI32>,
+    pub property_overlap: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
+    pub property_scale: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorListString>,
+    pub property_bins: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorI32>,
+    pub property_output: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorListString>,
+    pub property_decibels_floor: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
+    pub property_decibels_ceiling: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
+    pub property_auto_gain: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorBool>,
+    pub property_auto_gain_half_life: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
+    pub property_spectrogram_length: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorI32>,
+}
+
+// Does not implement EffectParamCustom because of different argument requirements
+impl EffectParamCustomFFT {
+    pub fn new<'a>(
+        param: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeTexture>>>,
+        param_previous: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeTexture>>>,
+        param_spectrogram: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeTexture>>>,
+        param_spectrogram_row: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeInt>>>,
+        identifier: &str,
+        settings: &mut SettingsContext,
+        preprocess_result: &PreprocessResult,
+    ) -> Result<Self, Cow<'static, str>> {
+        let audio_input = AudioInputProperties::new(identifier, settings, preprocess_result)?;
+        let property_channel_layout = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: "Single".to_string(),
+                default_descriptor_specialization: PropertyDescriptorSpecializationListString::from_names(FFTChannelLayout::NAMES),
+            },
+            identifier,
+            Some("channel_layout"),
+            preprocess_result,
+            settings,
+        )?;
+        let dampening = DampeningProperties::new(identifier, settings, preprocess_result)?;
+
+        let property_window = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: "Hanning".to_string(),
+                default_descriptor_specialization: PropertyDescriptorSpecializationListString::from_names(WindowFunction::NAMES),
+            },
+            identifier,
+            Some("window"),
+            preprocess_result,
+            settings,
+        )?;
+        // Coefficients of the `Cosine` window function, defaults equivalent to the `Hanning` window function
+        let property_window_cosine_coefficients = [("a", 0.5), ("b", 0.5), ("c", 0.0), ("d", 0.0)].iter()
+            .map(|(coefficient, default_value)| {
+                <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+                    LoadedValueTypePropertyArgs {
+                        allow_definitions_in_source: true,
+                        default_value: *default_value,
+                        default_descriptor_specialization: PropertyDescriptorSpecializationF64 {
+                            min: std::f64::MIN,
+                            max: std::f64::MAX,
+                            step: 0.01,
+                            slider: false,
+                        },
+                    },
+                    identifier,
+                    Some(&format!("window_cosine_{}", coefficient)),
+                    preprocess_result,
+                    settings,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let property_fft_size = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: 2048,
+                // The powers of two from 512 to 16384
+                default_descriptor_specialization: PropertyDescriptorSpecializationListI32 {
+                    items: (9..=14)
+                        .map(|exponent| {
+                            let fft_size = 1 << exponent;
+
+                            (CString::new(fft_size.to_string()).unwrap(), fft_size)
+                        })
+                        .collect(),
+                },
+            },
+            identifier,
+            Some("fft_size"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_overlap = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: 50.0,
+                default_descriptor_specialization: PropertyDescriptorSpecializationF64 {
+                    min: 0.0,
+                    max: 95.0,
+                    step: 0.01,
+                    slider: true,
+                },
+            },
+            identifier,
+            Some("overlap"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_scale = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: "Linear".to_string(),
+                default_descriptor_specialization: PropertyDescriptorSpecializationListString::from_names(FrequencyScale::NAMES),
+            },
+            identifier,
+            Some("scale"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_bins = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: 0,
+                default_descriptor_specialization: PropertyDescriptorSpecializationI32 {
+                    min: 0,
+                    max: 16384,
+                    step: 1,
+                    slider: false,
+                },
+            },
+            identifier,
+            Some("bins"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_output = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: "AmplitudeSqrt".to_string(),
+                default_descriptor_specialization: PropertyDescriptorSpecializationListString::from_names(FFTOutput::NAMES),
+            },
+            identifier,
+            Some("output"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_decibels_floor = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: -60.0,
+                default_descriptor_specialization: PropertyDescriptorSpecializationF64 {
+                    min: -200.0,
+                    max: 0.0,
+                    step: 0.1,
+                    slider: false,
+                },
+            },
+            identifier,
+            Some("decibels_floor"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_decibels_ceiling = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: 0.0,
+                default_descriptor_specialization: PropertyDescriptorSpecializationF64 {
+                    min: -200.0,
+                    max: 0.0,
+                    step: 0.1,
+                    slider: false,
+                },
+            },
+            identifier,
+            Some("decibels_ceiling"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_auto_gain = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: false,
+                default_descriptor_specialization: PropertyDescriptorSpecializationBool {},
+            },
+            identifier,
+            Some("auto_gain"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_auto_gain_half_life = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: 1.0,
+                default_descriptor_specialization: PropertyDescriptorSpecializationF64 {
+                    min: 0.01,
+                    max: 60.0,
+                    step: 0.01,
+                    slider: false,
+                },
+            },
+            identifier,
+            Some("auto_gain_half_life"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_spectrogram_length = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: 256,
+                default_descriptor_specialization: PropertyDescriptorSpecializationI32 {
+                    min: 1,
+                    max: 4096,
+                    step: 1,
+                    slider: false,
+                },
+            },
+            identifier,
+            Some("spectrogram_length"),
+            preprocess_result,
+            settings,
+        )?;
+
+        let mut result = Self {
+            effect_param: param.map(|param| EffectParam::new(param.disable())),
+            effect_param_previous: param_previous.map(|param_previous| EffectParam::new(param_previous.disable())),
+            spectrogram: param_spectrogram.map(|param_spectrogram| FFTSpectrogram::new(
+                EffectParam::new(param_spectrogram.disable()),
+                param_spectrogram_row.map(|param_spectrogram_row| EffectParam::new(param_spectrogram_row.disable())),
+            )),
+            audio_ffts: Vec::new(),
+            audio_input,
+            property_channel_layout,
+            dampening,
+            property_window,
+            property_window_cosine_coefficients,
+            property_fft_size,
+            property_overlap,
+            property_scale,
+            property_bins,
+            property_output,
+            property_decibels_floor,
+            property_decibels_ceiling,
+            property_auto_gain,
+            property_auto_gain_half_life,
+            property_spectrogram_length,
+        };
+
+        if result.get_window_function().is_none() {
+            throw!(format!(
+                "Invalid window function `{}`, expected one of: {}",
+                result.property_window.get_value(),
+                WindowFunction::NAMES.join(", "),
+            ));
+        }
+
+        if result.get_channel_layout().is_none() {
+            throw!(format!(
+                "Invalid channel layout `{}`, expected one of: {}",
+                result.property_channel_layout.get_value(),
+                FFTChannelLayout::NAMES.join(", "),
+            ));
+        }
+
+        if result.property_fft_size.is_hardcoded() && !(result.property_fft_size.get_value() as u32).is_power_of_two() {
+            throw!(format!(
+                "The FFT size must be a power of two, got {}.",
+                result.property_fft_size.get_value(),
+            ));
+        }
+
+        if result.get_frequency_scale().is_none() {
+            throw!(format!(
+                "Invalid frequency scale `{}`, expected one of: {}",
+                result.property_scale.get_value(),
+                FrequencyScale::NAMES.join(", "),
+            ));
+        }
+
+        if result.get_output().is_none() {
+            throw!(format!(
+                "Invalid FFT output `{}`, expected one of: {}",
+                result.property_output.get_value(),
+                FFTOutput::NAMES.join(", "),
+            ));
+        }
+
+        // Ranges made empty in the UI are clamped by `get_output` instead
+        if result.property_decibels_floor.is_hardcoded() && result.property_decibels_ceiling.is_hardcoded()
+            && result.property_decibels_floor.get_value() >= result.property_decibels_ceiling.get_value() {
+            throw!("The decibel floor must be lower than the decibel ceiling.");
+        }
+
+        result.request_audio_fft();
+
+        Ok(result)
+    }
+
+    fn get_window_function(&self) -> Option<WindowFunction> {
+        let coefficients = &self.property_window_cosine_coefficients;
+
+        WindowFunction::from_name(
+            &self.property_window.get_value(),
+            [
+                coefficients[0].get_value(),
+                coefficients[1].get_value(),
+                coefficients[2].get_value(),
+                coefficients[3].get_value(),
+            ],
+        )
+    }
+
+    fn get_channel_layout(&self) -> Option<FFTChannelLayout> {
+        FFTChannelLayout::from_name(&self.property_channel_layout.get_value())
+    }
+
+    fn get_frequency_scale(&self) -> Option<FrequencyScale> {
+        FrequencyScale::from_name(&self.property_scale.get_value())
+    }
+
+    fn get_output(&self) -> Option<FFTOutput> {
+        FFTOutput::from_name(
+            &self.property_output.get_value(),
+            self.property_decibels_floor.get_value(),
+            // Prevent a division by zero, if the range was made empty in the UI
+            self.property_decibels_ceiling.get_value().max(self.property_decibels_floor.get_value() + 0.1),
+        )
+    }
+
+    fn request_audio_fft(&mut self) {
+        let channels = self.get_channel_layout()
+            .unwrap_or(FFTChannelLayout::Single)
+            .channels(self.audio_input.channel());
+
+        self.audio_ffts = channels.into_iter()
+            .map(|channel| GLOBAL_STATE.request_audio_fft(&self.get_audio_fft_descriptor(channel)))
+            .collect();
+    }
+
+    fn get_audio_fft_descriptor(&self, channel: usize) -> GlobalStateAudioFFTDescriptor {
+        GlobalStateAudioFFTDescriptor::new(
+            self.audio_input.descriptor(),
+            channel,
+            self.dampening.attack(),
+            self.dampening.release(),
+            self.get_window_function().unwrap_or(WindowFunction::Hanning),
+            // Round down to a power of two, if the settings contain a value missing from the list
+            (self.property_fft_size.get_value().max(1) as usize + 1).next_power_of_two() / 2,
+            self.property_overlap.get_value() / 100.0,
+            self.get_output().unwrap_or(FFTOutput::AmplitudeSqrt),
+            if self.property_auto_gain.get_value() {
+                Some(self.property_auto_gain_half_life.get_value())
+            } else {
+                None
+            },
+        )
+    }
+}
+
+impl BindableProperty for EffectParamCustomFFT {
+    fn add_properties(&self, properties: &mut Properties) {
+        self.audio_input.add_properties(properties);
+        self.property_channel_layout.add_properties(properties);
+        self.dampening.add_properties(properties);
+        self.property_window.add_properties(properties);
+        self.property_window_cosine_coefficients.iter()
+            .for_each(|property| property.add_properties(properties));
+        self.property_fft_size.add_properties(properties);
+        self.property_overlap.add_properties(properties);
+        self.property_scale.add_properties(properties);
+        self.property_bins.add_properties(properties);
+        self.property_output.add_properties(properties);
+        self.property_decibels_floor.add_properties(properties);
+        self.property_decibels_ceiling.add_properties(properties);
+        self.property_auto_gain.add_properties(properties);
+        self.property_auto_gain_half_life.add_properties(properties);
+
+        if self.spectrogram.is_some() {
+            self.property_spectrogram_length.add_properties(properties);
+        }
+    }
+
+    fn reload_settings(&mut self, settings: &mut SettingsContext) {
+        self.audio_input.reload_settings(settings);
+        self.property_channel_layout.reload_settings(settings);
+        self.dampening.reload_settings(settings);
+        self.property_window.reload_settings(settings);
+        self.property_window_cosine_coefficients.iter_mut()
+            .for_each(|property| property.reload_settings(settings));
+        self.property_fft_size.reload_settings(settings);
+        self.property_overlap.reload_settings(settings);
+        self.property_scale.reload_settings(settings);
+        self.property_bins.reload_settings(settings);
+        self.property_output.reload_settings(settings);
+        self.property_decibels_floor.reload_settings(settings);
+        self.property_decibels_ceiling.reload_settings(settings);
+        self.property_auto_gain.reload_settings(settings);
+        self.property_auto_gain_half_life.reload_settings(settings);
+        self.property_spectrogram_length.reload_settings(settings);
+        self.request_audio_fft();
+    }
+
+    fn prepare_values(&mut self) {
+        let fft_results = if let Some(results) = self.audio_ffts.iter()
+            .map(|audio_fft| audio_fft.retrieve_result())
+            .collect::<Option<Vec<_>>>() {
+            results
+        } else {
+            return;
+        };
+        let frequency_scale = self.get_frequency_scale().unwrap_or(FrequencyScale::Linear);
+        let bins = self.property_bins.get_value().max(0) as usize;
+        let nyquist_frequency = GlobalStateAudioFFT::get_nyquist_frequency();
+        let frequency_spectra = fft_results.iter()
+            .map(|fft_result| frequency_scale.resample(&fft_result.frequency_spectrum, bins, nyquist_frequency))
+            .collect::<Vec<_>>();
+
+        // Spectra of channels analyzed with a different FFT size are not packed together.
+        if frequency_spectra.is_empty() || frequency_spectra.iter().any(|spectrum| spectrum.len() != frequency_spectra[0].len()) {
+            return;
+        }
+
+        let channel_layout = self.get_channel_layout().unwrap_or(FFTChannelLayout::Single);
+        let (texels, dimensions, components) = channel_layout.pack(&frequency_spectra);
+
+        if let Some(spectrogram) = self.spectrogram.as_mut() {
+            spectrogram.push(
+                &texels,
+                dimensions,
+                components,
+                fft_results[0].batch_number,
+                self.property_spectrogram_length.get_value().max(1) as usize,
+            );
+        }
+
+        let effect_param = if let Some(effect_param) = self.effect_param.as_mut() {
+            effect_param
+        } else {
+            return;
+        };
+        let texture_data = unsafe {
+            std::slice::from_raw_parts::<u8>(
+                texels.as_ptr() as *const _,
+                texels.len() * std::mem::size_of::<f32>(),
+            )
+        }.iter().copied().collect::<Vec<_>>();
+        let texture_fft = TextureDescriptor {
+            dimensions,
+            color_format: if components == 4 {
+                ColorFormatKind::RGBA32F
+            } else {
+                ColorFormatKind::R32F
+            },
+            levels: smallvec![texture_data],
+            flags: 0,
+        };
+
+        effect_param.prepare_value(texture_fft);
+    }
+
+    fn stage_value<'a>(&mut self, graphics_context: &'a GraphicsContext) {
+        if let Some(effect_param) = self.effect_param.as_mut() {
+            if let Some(effect_param_previous) = self.effect_param_previous.as_mut() {
+                if let Some(previous_texture_fft) = effect_param.take_staged_value() {
+                    effect_param_previous.stage_value_custom(previous_texture_fft, graphics_context);
+                }
+            }
+
+            effect_param.stage_value(graphics_context);
+        }
+
+        if let Some(spectrogram) = self.spectrogram.as_mut() {
+            spectrogram.stage_value(graphics_context);
+        }
+    }
+
+    fn assign_value<'a>(&mut self, graphics_context: &'a FilterContext) {
+        if let Some(effect_param_previous) = self.effect_param_previous.as_mut() {
+            effect_param_previous.assign_value_if_staged(graphics_context);
+        }
+        if let Some(effect_param) = self.effect_param.as_mut() {
+            effect_param.assign_value_if_staged(graphics_context);
+        }
+        if let Some(spectrogram) = self.spectrogram.as_mut() {
+            spectrogram.assign_value(graphics_context);
+        }
+    }
+
+    fn enable_and_drop(self, graphics_context: &GraphicsContext) {
+        if let Some(effect_param_previous) = self.effect_param_previous {
+            effect_param_previous.enable_and_drop(graphics_context);
+        }
+        if let Some(effect_param) = self.effect_param {
+            effect_param.enable_and_drop(graphics_context);
+        }
+        if let Some(spectrogram) = self.spectrogram {
+            spectrogram.enable_and_drop(graphics_context);
+        }
+    }
+}
+
+pub struct EffectParamCustomAudioWaveform {
+    pub effect_param: EffectParamTexture,
+    pub effect_param_previous: Option<EffectParamTexture>,
+    pub audio_waveform: Option<Arc<GlobalStateAudioWaveform>>,
+    pub audio_input: AudioInputProperties,
+    pub property_length: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorI32>,
+    pub property_stereo: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorBool>,
+}
+
+// Does not implement EffectParamCustom because of different argument requirements
+impl EffectParamCustomAudioWaveform {
+    pub fn new<'a>(
+        param: GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeTexture>>,
+        param_previous: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeTexture>>>,
+        identifier: &str,
+        settings: &mut SettingsContext,
+        preprocess_result: &PreprocessResult,
+    ) -> Result<Self, Cow<'static, str>> {
+        let audio_input = AudioInputProperties::new(identifier, settings, preprocess_result)?;
+        let property_length = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: 1024,
+                default_descriptor_specialization: PropertyDescriptorSpecializationI32 {
+                    min: 1,
+                    max: 16384,
+                    step: 1,
+                    slider: false,
+                },
+            },
+            identifier,
+            Some("length"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_stereo = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: false,
+                default_descriptor_specialization: PropertyDescriptorSpecializationBool {},
+            },
+            identifier,
+            Some("stereo"),
+            preprocess_result,
+            settings,
+        )?;
+
+        let mut result = Self {
+            effect_param: EffectParam::new(param.disable()),
+            effect_param_previous: param_previous.map(|param_previous| EffectParam::new(param_previous.disable())),
+            audio_waveform: None,
+            audio_input,
+            property_length,
+            property_stereo,
+        };
+
+        result.request_audio_waveform();
+
+        Ok(result)
+    }
+
+    fn request_audio_waveform(&mut self) {
+        let audio_waveform_descriptor = GlobalStateAudioWaveformDescriptor::new(
+            self.audio_input.descriptor(),
+            self.audio_input.channel(),
+            self.property_length.get_value().max(1) as usize,
+            self.property_stereo.get_value(),
+        );
+
+        self.audio_waveform = Some(GLOBAL_STATE.request_audio_waveform(&audio_waveform_descriptor));
+    }
+}
+
+impl BindableProperty for EffectParamCustomAudioWaveform {
+    fn add_properties(&self, properties: &mut Properties) {
+        self.audio_input.add_properties(properties);
+        self.property_length.add_properties(properties);
+        self.property_stereo.add_properties(properties);
+    }
+
+    fn reload_settings(&mut self, settings: &mut SettingsContext) {
+        self.audio_input.reload_settings(settings);
+        self.property_length.reload_settings(settings);
+        self.property_stereo.reload_settings(settings);
+        self.request_audio_waveform();
+    }
+
+    fn prepare_values(&mut self) {
+        let waveform_result = if let Some(result) = self.audio_waveform.as_mut().unwrap().retrieve_result() {
+            result
+        } else {
+            return;
+        };
+        let samples = &waveform_result.samples;
         let texture_data = unsafe {
             std::slice::from_raw_parts::<u8>(
-                frequency_spectrum.as_ptr() as *const _,
-                frequency_spectrum.len() * std::mem::size_of::<f32>(),
+                samples.as_ptr() as *const _,
+                samples.len() * std::mem::size_of::<f32>(),
             )
         }.iter().copied().collect::<Vec<_>>();
-        let texture_fft = TextureDescriptor {
-            dimensions: [frequency_spectrum.len(), 1],
-            color_format: ColorFormatKind::R32F,
+        let texture_waveform = TextureDescriptor {
+            dimensions: [samples.len() / waveform_result.channels, 1],
+            color_format: if waveform_result.channels == 2 {
+                ColorFormatKind::RG32F
+            } else {
+                ColorFormatKind::R32F
+            },
             levels: smallvec![texture_data],
             flags: 0,
         };
 
-        self.effect_param.prepare_value(texture_fft);
+        self.effect_param.prepare_value(texture_waveform);
     }
 
     fn stage_value<'a>(&mut self, graphics_context: &'a GraphicsContext) {
         if let Some(effect_param_previous) = self.effect_param_previous.as_mut() {
-            if let Some(previous_texture_fft) = self.effect_param.take_staged_value() {
-                effect_param_previous.stage_value_custom(previous_texture_fft, graphics_context);
+            if let Some(previous_texture_waveform) = self.effect_param.take_staged_value() {
+                effect_param_previous.stage_value_custom(previous_texture_waveform, graphics_context);
             }
         }
 
@@ -650,3 +2167,497 @@ impl BindableProperty for EffectParamCustomFFT {
         self.effect_param.enable_and_drop(graphics_context);
     }
 }
+
+pub struct EffectParamCustomBeat {
+    pub effect_param_trigger: Option<EffectParamFloat>,
+    pub effect_param_time: Option<EffectParamFloat>,
+    pub effect_param_strength: Option<EffectParamFloat>,
+    pub effect_param_bpm: Option<EffectParamFloat>,
+    pub beat_detector: Option<Arc<GlobalStateBeatDetector>>,
+    /// The beat count of the previous frame, used to trigger once per beat.
+    pub beat_count: Option<usize>,
+    pub audio_input: AudioInputProperties,
+    pub property_frequency_min: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
+    pub property_frequency_max: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
+    pub property_threshold: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
+    pub property_min_interval: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
+}
+
+// Does not implement EffectParamCustom because of different argument requirements
+impl EffectParamCustomBeat {
+    pub fn new<'a>(
+        param_trigger: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeFloat>>>,
+        param_time: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeFloat>>>,
+        param_strength: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeFloat>>>,
+        param_bpm: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeFloat>>>,
+        identifier: &str,
+        settings: &mut SettingsContext,
+        preprocess_result: &PreprocessResult,
+    ) -> Result<Self, Cow<'static, str>> {
+        let audio_input = AudioInputProperties::new(identifier, settings, preprocess_result)?;
+        let property_frequency_min = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: 20.0,
+                default_descriptor_specialization: PropertyDescriptorSpecializationF64 {
+                    min: 0.0,
+                    max: 24000.0,
+                    step: 1.0,
+                    slider: false,
+                },
+            },
+            identifier,
+            Some("frequency_min"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_frequency_max = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: 150.0,
+                default_descriptor_specialization: PropertyDescriptorSpecializationF64 {
+                    min: 0.0,
+                    max: 24000.0,
+                    step: 1.0,
+                    slider: false,
+                },
+            },
+            identifier,
+            Some("frequency_max"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_threshold = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: 1.5,
+                default_descriptor_specialization: PropertyDescriptorSpecializationF64 {
+                    min: 1.0,
+                    max: 10.0,
+                    step: 0.01,
+                    slider: true,
+                },
+            },
+            identifier,
+            Some("threshold"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_min_interval = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: 0.2,
+                default_descriptor_specialization: PropertyDescriptorSpecializationF64 {
+                    min: 0.0,
+                    max: 10.0,
+                    step: 0.01,
+                    slider: false,
+                },
+            },
+            identifier,
+            Some("min_interval"),
+            preprocess_result,
+            settings,
+        )?;
+
+        // Bands made empty in the UI are corrected by `get_frequency_band` instead
+        if property_frequency_min.is_hardcoded() && property_frequency_max.is_hardcoded()
+            && property_frequency_min.get_value() >= property_frequency_max.get_value() {
+            throw!("The minimum frequency must be lower than the maximum frequency.");
+        }
+
+        let mut result = Self {
+            effect_param_trigger: param_trigger.map(|param| EffectParam::new(param.disable())),
+            effect_param_time: param_time.map(|param| EffectParam::new(param.disable())),
+            effect_param_strength: param_strength.map(|param| EffectParam::new(param.disable())),
+            effect_param_bpm: param_bpm.map(|param| EffectParam::new(param.disable())),
+            beat_detector: None,
+            beat_count: None,
+            audio_input,
+            property_frequency_min,
+            property_frequency_max,
+            property_threshold,
+            property_min_interval,
+        };
+
+        result.request_beat_detector();
+
+        Ok(result)
+    }
+
+    /// The analyzed frequency band, swapped and widened to at least 1 Hz, if the bounds were mixed up in the UI.
+    fn get_frequency_band(&self) -> (f64, f64) {
+        let (first, second) = (self.property_frequency_min.get_value(), self.property_frequency_max.get_value());
+        let frequency_min = first.min(second);
+
+        (frequency_min, first.max(second).max(frequency_min + 1.0))
+    }
+
+    fn request_beat_detector(&mut self) {
+        let (frequency_min, frequency_max) = self.get_frequency_band();
+        let beat_detector_descriptor = GlobalStateBeatDetectorDescriptor::new(
+            self.audio_input.descriptor(),
+            self.audio_input.channel(),
+            frequency_min,
+            frequency_max,
+            self.property_threshold.get_value(),
+            self.property_min_interval.get_value(),
+        );
+
+        self.beat_detector = Some(GLOBAL_STATE.request_beat_detector(&beat_detector_descriptor));
+    }
+
+    fn effect_params_mut(&mut self) -> impl Iterator<Item=&mut EffectParamFloat> {
+        self.effect_param_trigger.iter_mut()
+            .chain(self.effect_param_time.iter_mut())
+            .chain(self.effect_param_strength.iter_mut())
+            .chain(self.effect_param_bpm.iter_mut())
+    }
+}
+
+impl BindableProperty for EffectParamCustomBeat {
+    fn add_properties(&self, properties: &mut Properties) {
+        self.audio_input.add_properties(properties);
+        self.property_frequency_min.add_properties(properties);
+        self.property_frequency_max.add_properties(properties);
+        self.property_threshold.add_properties(properties);
+        self.property_min_interval.add_properties(properties);
+    }
+
+    fn reload_settings(&mut self, settings: &mut SettingsContext) {
+        self.audio_input.reload_settings(settings);
+        self.property_frequency_min.reload_settings(settings);
+        self.property_frequency_max.reload_settings(settings);
+        self.property_threshold.reload_settings(settings);
+        self.property_min_interval.reload_settings(settings);
+        self.request_beat_detector();
+        self.beat_count = None;
+    }
+
+    fn prepare_values(&mut self) {
+        let beat_result = if let Some(result) = self.beat_detector.as_mut().unwrap().retrieve_result() {
+            result
+        } else {
+            return;
+        };
+        let triggered = self.beat_count.map(|beat_count| beat_count != beat_result.beat_count).unwrap_or(false);
+
+        self.beat_count = Some(beat_result.beat_count);
+
+        if let Some(effect_param_trigger) = self.effect_param_trigger.as_mut() {
+            effect_param_trigger.prepare_value(if triggered { 1.0 } else { 0.0 });
+        }
+
+        if let Some(effect_param_time) = self.effect_param_time.as_mut() {
+            effect_param_time.prepare_value(beat_result.time_since_beat);
+        }
+
+        if let Some(effect_param_strength) = self.effect_param_strength.as_mut() {
+            effect_param_strength.prepare_value(beat_result.onset_strength);
+        }
+
+        if let Some(effect_param_bpm) = self.effect_param_bpm.as_mut() {
+            effect_param_bpm.prepare_value(beat_result.bpm);
+        }
+    }
+
+    fn stage_value<'a>(&mut self, graphics_context: &'a GraphicsContext) {
+        self.effect_params_mut().for_each(|effect_param| effect_param.stage_value(graphics_context));
+    }
+
+    fn assign_value<'a>(&mut self, graphics_context: &'a FilterContext) {
+        self.effect_params_mut().for_each(|effect_param| effect_param.assign_value_if_staged(graphics_context));
+    }
+
+    fn enable_and_drop(self, graphics_context: &GraphicsContext) {
+        self.effect_param_trigger.into_iter()
+            .chain(self.effect_param_time)
+            .chain(self.effect_param_strength)
+            .chain(self.effect_param_bpm)
+            .for_each(|effect_param| effect_param.enable_and_drop(graphics_context));
+    }
+}
+
+pub struct EffectParamCustomAudioLevel {
+    pub effect_param_rms: Option<EffectParamFloat>,
+    pub effect_param_rms_db: Option<EffectParamFloat>,
+    pub effect_param_peak: Option<EffectParamFloat>,
+    pub effect_param_peak_db: Option<EffectParamFloat>,
+    pub audio_level: Option<Arc<GlobalStateAudioLevel>>,
+    pub audio_input: AudioInputProperties,
+    pub dampening: DampeningProperties,
+}
+
+// Does not implement EffectParamCustom because of different argument requirements
+impl EffectParamCustomAudioLevel {
+    pub fn new<'a>(
+        param_rms: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeFloat>>>,
+        param_rms_db: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeFloat>>>,
+        param_peak: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeFloat>>>,
+        param_peak_db: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeFloat>>>,
+        identifier: &str,
+        settings: &mut SettingsContext,
+        preprocess_result: &PreprocessResult,
+    ) -> Result<Self, Cow<'static, str>> {
+        let audio_input = AudioInputProperties::new(identifier, settings, preprocess_result)?;
+        let dampening = DampeningProperties::new(identifier, settings, preprocess_result)?;
+
+        let mut result = Self {
+            effect_param_rms: param_rms.map(|param| EffectParam::new(param.disable())),
+            effect_param_rms_db: param_rms_db.map(|param| EffectParam::new(param.disable())),
+            effect_param_peak: param_peak.map(|param| EffectParam::new(param.disable())),
+            effect_param_peak_db: param_peak_db.map(|param| EffectParam::new(param.disable())),
+            audio_level: None,
+            audio_input,
+            dampening,
+        };
+
+        result.request_audio_level();
+
+        Ok(result)
+    }
+
+    fn request_audio_level(&mut self) {
+        let audio_level_descriptor = GlobalStateAudioLevelDescriptor::new(
+            self.audio_input.descriptor(),
+            self.audio_input.channel(),
+            self.dampening.attack(),
+            self.dampening.release(),
+        );
+
+        self.audio_level = Some(GLOBAL_STATE.request_audio_level(&audio_level_descriptor));
+    }
+
+    fn effect_params_mut(&mut self) -> impl Iterator<Item=&mut EffectParamFloat> {
+        self.effect_param_rms.iter_mut()
+            .chain(self.effect_param_rms_db.iter_mut())
+            .chain(self.effect_param_peak.iter_mut())
+            .chain(self.effect_param_peak_db.iter_mut())
+    }
+}
+
+impl BindableProperty for EffectParamCustomAudioLevel {
+    fn add_properties(&self, properties: &mut Properties) {
+        self.audio_input.add_properties(properties);
+        self.dampening.add_properties(properties);
+    }
+
+    fn reload_settings(&mut self, settings: &mut SettingsContext) {
+        self.audio_input.reload_settings(settings);
+        self.dampening.reload_settings(settings);
+        self.request_audio_level();
+    }
+
+    fn prepare_values(&mut self) {
+        let level_result = if let Some(result) = self.audio_level.as_mut().unwrap().retrieve_result() {
+            result
+        } else {
+            return;
+        };
+
+        if let Some(effect_param_rms) = self.effect_param_rms.as_mut() {
+            effect_param_rms.prepare_value(level_result.rms);
+        }
+
+        if let Some(effect_param_rms_db) = self.effect_param_rms_db.as_mut() {
+            effect_param_rms_db.prepare_value(level_result.rms_decibels());
+        }
+
+        if let Some(effect_param_peak) = self.effect_param_peak.as_mut() {
+            effect_param_peak.prepare_value(level_result.peak);
+        }
+
+        if let Some(effect_param_peak_db) = self.effect_param_peak_db.as_mut() {
+            effect_param_peak_db.prepare_value(level_result.peak_decibels());
+        }
+    }
+
+    fn stage_value<'a>(&mut self, graphics_context: &'a GraphicsContext) {
+        self.effect_params_mut().for_each(|effect_param| effect_param.stage_value(graphics_context));
+    }
+
+    fn assign_value<'a>(&mut self, graphics_context: &'a FilterContext) {
+        self.effect_params_mut().for_each(|effect_param| effect_param.assign_value_if_staged(graphics_context));
+    }
+
+    fn enable_and_drop(self, graphics_context: &GraphicsContext) {
+        self.effect_param_rms.into_iter()
+            .chain(self.effect_param_rms_db)
+            .chain(self.effect_param_peak)
+            .chain(self.effect_param_peak_db)
+            .for_each(|effect_param| effect_param.enable_and_drop(graphics_context));
+    }
+}
+
+pub struct EffectParamCustomAudioPitch {
+    pub effect_param_frequency: Option<EffectParamFloat>,
+    pub effect_param_confidence: Option<EffectParamFloat>,
+    pub effect_param_note: Option<EffectParamFloat>,
+    pub pitch_detector: Option<Arc<GlobalStatePitchDetector>>,
+    pub audio_input: AudioInputProperties,
+    pub property_frequency_min: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
+    pub property_frequency_max: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
+    pub property_threshold: LoadedValueTypeProperty<LoadedValueTypePropertyDescriptorF64>,
+}
+
+// Does not implement EffectParamCustom because of different argument requirements
+impl EffectParamCustomAudioPitch {
+    pub fn new<'a>(
+        param_frequency: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeFloat>>>,
+        param_confidence: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeFloat>>>,
+        param_note: Option<GraphicsContextDependentEnabled<'a, GraphicsEffectParamTyped<ShaderParamTypeFloat>>>,
+        identifier: &str,
+        settings: &mut SettingsContext,
+        preprocess_result: &PreprocessResult,
+    ) -> Result<Self, Cow<'static, str>> {
+        let audio_input = AudioInputProperties::new(identifier, settings, preprocess_result)?;
+        let property_frequency_min = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: 60.0,
+                default_descriptor_specialization: PropertyDescriptorSpecializationF64 {
+                    min: 20.0,
+                    max: 4000.0,
+                    step: 1.0,
+                    slider: false,
+                },
+            },
+            identifier,
+            Some("frequency_min"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_frequency_max = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: 1000.0,
+                default_descriptor_specialization: PropertyDescriptorSpecializationF64 {
+                    min: 20.0,
+                    max: 4000.0,
+                    step: 1.0,
+                    slider: false,
+                },
+            },
+            identifier,
+            Some("frequency_max"),
+            preprocess_result,
+            settings,
+        )?;
+        let property_threshold = <LoadedValueTypeProperty<_> as LoadedValueType>::from(
+            LoadedValueTypePropertyArgs {
+                allow_definitions_in_source: true,
+                default_value: 0.15,
+                default_descriptor_specialization: PropertyDescriptorSpecializationF64 {
+                    min: 0.01,
+                    max: 1.0,
+                    step: 0.01,
+                    slider: true,
+                },
+            },
+            identifier,
+            Some("threshold"),
+            preprocess_result,
+            settings,
+        )?;
+
+        let mut result = Self {
+            effect_param_frequency: param_frequency.map(|param| EffectParam::new(param.disable())),
+            effect_param_confidence: param_confidence.map(|param| EffectParam::new(param.disable())),
+            effect_param_note: param_note.map(|param| EffectParam::new(param.disable())),
+            pitch_detector: None,
+            audio_input,
+            property_frequency_min,
+            property_frequency_max,
+            property_threshold,
+        };
+
+        // Ranges made empty in the UI are corrected by `get_frequency_range` instead
+        if result.property_frequency_min.is_hardcoded() && result.property_frequency_max.is_hardcoded()
+            && result.property_frequency_min.get_value() >= result.property_frequency_max.get_value() {
+            throw!("The minimum frequency must be lower than the maximum frequency.");
+        }
+
+        result.request_pitch_detector();
+
+        Ok(result)
+    }
+
+    /// The searched frequency range, swapped and widened to at least 1 Hz, if the bounds were mixed up in the UI.
+    fn get_frequency_range(&self) -> (f64, f64) {
+        let (first, second) = (self.property_frequency_min.get_value(), self.property_frequency_max.get_value());
+        let frequency_min = first.min(second);
+
+        (frequency_min, first.max(second).max(frequency_min + 1.0))
+    }
+
+    fn request_pitch_detector(&mut self) {
+        let (frequency_min, frequency_max) = self.get_frequency_range();
+        let pitch_detector_descriptor = GlobalStatePitchDetectorDescriptor::new(
+            self.audio_input.descriptor(),
+            self.audio_input.channel(),
+            frequency_min,
+            frequency_max,
+            self.property_threshold.get_value(),
+        );
+
+        self.pitch_detector = Some(GLOBAL_STATE.request_pitch_detector(&pitch_detector_descriptor));
+    }
+
+    fn effect_params_mut(&mut self) -> impl Iterator<Item=&mut EffectParamFloat> {
+        self.effect_param_frequency.iter_mut()
+            .chain(self.effect_param_confidence.iter_mut())
+            .chain(self.effect_param_note.iter_mut())
+    }
+}
+
+impl BindableProperty for EffectParamCustomAudioPitch {
+    fn add_properties(&self, properties: &mut Properties) {
+        self.audio_input.add_properties(properties);
+        self.property_frequency_min.add_properties(properties);
+        self.property_frequency_max.add_properties(properties);
+        self.property_threshold.add_properties(properties);
+    }
+
+    fn reload_settings(&mut self, settings: &mut SettingsContext) {
+        self.audio_input.reload_settings(settings);
+        self.property_frequency_min.reload_settings(settings);
+        self.property_frequency_max.reload_settings(settings);
+        self.property_threshold.reload_settings(settings);
+        self.request_pitch_detector();
+    }
+
+    fn prepare_values(&mut self) {
+        let pitch_result = if let Some(result) = self.pitch_detector.as_mut().unwrap().retrieve_result() {
+            result
+        } else {
+            return;
+        };
+
+        if let Some(effect_param_frequency) = self.effect_param_frequency.as_mut() {
+            effect_param_frequency.prepare_value(pitch_result.frequency);
+        }
+
+        if let Some(effect_param_confidence) = self.effect_param_confidence.as_mut() {
+            effect_param_confidence.prepare_value(pitch_result.confidence);
+        }
+
+        if let Some(effect_param_note) = self.effect_param_note.as_mut() {
+            effect_param_note.prepare_value(pitch_result.note);
+        }
+    }
+
+    fn stage_value<'a>(&mut self, graphics_context: &'a GraphicsContext) {
+        self.effect_params_mut().for_each(|effect_param| effect_param.stage_value(graphics_context));
+    }
+
+    fn assign_value<'a>(&mut self, graphics_context: &'a FilterContext) {
+        self.effect_params_mut().for_each(|effect_param| effect_param.assign_value_if_staged(graphics_context));
+    }
+
+    fn enable_and_drop(self, graphics_context: &GraphicsContext) {
+        self.effect_param_frequency.into_iter()
+            .chain(self.effect_param_confidence)
+            .chain(self.effect_param_note)
+            .for_each(|effect_param| effect_param.enable_and_drop(graphics_context));
+    }
+}
@@ -439,6 +439,64 @@ impl LoadedValueTypePropertyDescriptor for LoadedValueTypePropertyDescriptorBool
     }
 }
 
+/// A property descriptor with only a description, of which the specialization is provided by
+/// `default_descriptor_specialization`, rather than being loaded from the shader source code.
+#[derive(Debug)]
+pub struct LoadedValueTypePropertyDescriptorSpecialized<S>
+    where S: PropertyDescriptorSpecialization + Clone + Debug,
+{
+    descriptor: PropertyDescriptor<S>,
+    description: LoadedValueTypeSource<String>,
+}
+
+pub type LoadedValueTypePropertyDescriptorPath = LoadedValueTypePropertyDescriptorSpecialized<PropertyDescriptorSpecializationPath>;
+pub type LoadedValueTypePropertyDescriptorSource = LoadedValueTypePropertyDescriptorSpecialized<PropertyDescriptorSpecializationSource>;
+pub type LoadedValueTypePropertyDescriptorListString = LoadedValueTypePropertyDescriptorSpecialized<PropertyDescriptorSpecializationListString>;
+/// A drop-down list of named integer values, parsed from `<NAME>__options` by the owner of the property.
+pub type LoadedValueTypePropertyDescriptorListI32 = LoadedValueTypePropertyDescriptorSpecialized<PropertyDescriptorSpecializationListI32>;
+
+impl<S> LoadedValueTypePropertyDescriptor for LoadedValueTypePropertyDescriptorSpecialized<S>
+    where S: PropertyDescriptorSpecialization + Clone + Debug,
+{
+    type Specialization = S;
+
+    fn from_identifier(
+        args: LoadedValueTypePropertyDescriptorArgs<Self::Specialization>,
+        identifier: &str,
+        preprocess_result: &PreprocessResult,
+        settings: &mut SettingsContext,
+    ) -> Result<Self, Cow<'static, str>> {
+        let description = <LoadedValueTypeSource::<String> as LoadedValueType>::from(
+            LoadedValueTypeSourceArgs {
+                default_value: Some(identifier.to_string()),
+            },
+            identifier,
+            Some("description"),
+            preprocess_result,
+            settings,
+        )?;
+        let descriptor = PropertyDescriptor {
+            name: CString::new(identifier).unwrap(),
+            description: CString::new(description.get_value().unwrap()).unwrap(),
+            specialization: args.default_value,
+        };
+
+        Ok(Self {
+            descriptor,
+            description,
+        })
+    }
+
+    fn add_properties(&self, properties: &mut Properties) {
+        self.description.add_properties(properties);
+        properties.add_property(&self.descriptor);
+    }
+
+    fn get_value(&self) -> PropertyDescriptor<Self::Specialization> {
+        self.descriptor.clone()
+    }
+}
+
 pub trait LoadedValueTypePropertyBounds = LoadedValueTypePropertyDescriptor<Specialization: ValuePropertyDescriptorSpecialization<ValueType: FromStr + Clone>> + Debug;
 
 pub struct LoadedValueTypePropertyArgs<T: LoadedValueTypePropertyBounds> {
@@ -555,3 +613,29 @@ impl<T: LoadedValueTypePropertyBounds> LoadedValueType for LoadedValueTypeProper
         self.value.clone()
     }
 }
+
+impl<T: LoadedValueTypePropertyBounds> LoadedValueTypeProperty<T> {
+    /// Returns the descriptor of the property, unless the value is hardcoded in the shader source code.
+    pub fn get_descriptor(&self) -> Option<PropertyDescriptor<<T as LoadedValueTypePropertyDescriptor>::Specialization>> {
+        self.loaded_value_descriptor.as_ref().map(|descriptor| descriptor.get_value())
+    }
+
+    /// Returns `true`, if the value is hardcoded in the shader source code, rather than loaded from the settings.
+    pub fn is_hardcoded(&self) -> bool {
+        self.loaded_value_descriptor.is_none()
+    }
+
+    /// Changes the value and stores it in the settings.
+    /// Hardcoded values are changed until the effect is reloaded.
+    pub fn set_value(
+        &mut self,
+        value: <<T as LoadedValueTypePropertyDescriptor>::Specialization as ValuePropertyDescriptorSpecialization>::ValueType,
+        settings: &mut SettingsContext,
+    ) {
+        if let Some(descriptor) = self.get_descriptor() {
+            settings.set_property_value(&descriptor, value.clone());
+        }
+
+        self.value = value;
+    }
+}
@@ -2,18 +2,21 @@ use std::borrow::Cow;
 use std::path::PathBuf;
 use std::ffi::CString;
 use obs_wrapper::{
+    obs_sys::{gs_texture_t, obs_source_t},
     graphics::*,
     source::*,
 };
 use downcast::{impl_downcast, Downcast};
-use regex::Regex;
+use regex::{Regex, NoExpand, Captures};
 use crate::*;
 
 mod effect_param;
 mod loaded_value;
+mod transition;
 
 pub use effect_param::*;
 pub use loaded_value::*;
+pub use transition::*;
 
 /// An object representing a binding of setting-properties to graphics uniforms.
 pub trait BindableProperty: Downcast {
@@ -21,6 +24,14 @@ pub trait BindableProperty: Downcast {
     fn reload_settings(&mut self, settings: &mut SettingsContext);
     fn prepare_values(&mut self);
     fn stage_value<'a>(&mut self, graphics_context: &'a GraphicsContext);
+    /// Renders textures on the GPU, before the filter is processed.
+    fn render_textures(&mut self, _graphics_context: &GraphicsContext) {}
+    /// The hotkeys of the property, if enabled.
+    fn hotkeys_mut(&mut self) -> Option<&mut ParamHotkeys> { None }
+    /// Applies the hotkeys pressed since the last call, storing the changed values in the settings.
+    fn apply_hotkeys(&mut self, _settings: &mut SettingsContext) {}
+    /// The error which occurred while loading a resource of the property, such as an image file, if any.
+    fn load_error(&self) -> Option<&str> { None }
     fn assign_value<'a>(&mut self, graphics_context: &'a FilterContext);
     fn enable_and_drop(self, graphics_context: &GraphicsContext);
 }
@@ -35,6 +46,7 @@ pub struct EffectParamsCustom {
 impl EffectParamsCustom {
     pub fn from<'a>(
         mut params: HashMap<String, Indexed<GraphicsContextDependentEnabled<'a, GraphicsEffectParam>>>,
+        filter: *mut obs_source_t,
         settings: &mut SettingsContext,
         preprocess_result: &PreprocessResult,
     ) -> Result<Self, Cow<'static, str>> {
@@ -44,12 +56,70 @@ impl EffectParamsCustom {
 
         let result: Result<(), Cow<'static, str>> = try {
             {
-                let pattern_builtin_texture_fft = Regex::new(r"^builtin_texture_fft_(?P<field>\w+)$").unwrap();
+                let pattern_builtin_fft = Regex::new(r"^builtin_texture_(?:fft|spectrogram)_(?P<field>\w+)$").unwrap();
+                let pattern_field_previous = Regex::new(r"^.*_previous$").unwrap();
+                let mut field_names = params.keys()
+                    .filter_map(|param_name| pattern_builtin_fft.captures(param_name))
+                    .map(|captures| captures.name("field").unwrap().as_str().to_string())
+                    .filter(|field_name| !pattern_field_previous.is_match(field_name))
+                    .collect::<Vec<_>>();
+
+                field_names.sort_unstable();
+                field_names.dedup();
+
+                for field_name in &field_names {
+                    let mut take_param = |param_name: &str, param_type: ShaderParamTypeKind, param_type_name: &str| -> Result<Option<Indexed<GraphicsContextDependentEnabled<'a, GraphicsEffectParam>>>, Cow<'static, str>> {
+                        let param = params.remove(param_name);
+
+                        if let Some(ref param) = param.as_ref() {
+                            if param.param_type() != param_type {
+                                throw!(format!("Builtin field `{}` must be of type `{}`", param_name, param_type_name));
+                            }
+                        }
+
+                        Ok(param)
+                    };
+                    let param = take_param(&format!("builtin_texture_fft_{}", field_name), Texture, "texture2d")?;
+                    let param_previous = if param.is_some() {
+                        take_param(&format!("builtin_texture_fft_{}_previous", field_name), Texture, "texture2d")?
+                    } else {
+                        None
+                    };
+                    let param_spectrogram = take_param(&format!("builtin_texture_spectrogram_{}", field_name), Texture, "texture2d")?;
+                    let param_spectrogram_row = if param_spectrogram.is_some() {
+                        take_param(&format!("builtin_spectrogram_row_{}", field_name), Int, "int")?
+                    } else {
+                        None
+                    };
+                    let param_index = [&param, &param_spectrogram].iter()
+                        .filter_map(|param| param.as_ref().map(|param| param.index))
+                        .min()
+                        .unwrap();
+
+                    bound_params.push(
+                        Indexed {
+                            index: param_index,
+                            inner: Box::new(EffectParamCustomFFT::new(
+                                param.map(|param| param.into_inner().downcast().unwrap()),
+                                param_previous.map(|param_previous| param_previous.into_inner().downcast().unwrap()),
+                                param_spectrogram.map(|param_spectrogram| param_spectrogram.into_inner().downcast().unwrap()),
+                                param_spectrogram_row.map(|param_spectrogram_row| param_spectrogram_row.into_inner().downcast().unwrap()),
+                                field_name,
+                                settings,
+                                preprocess_result,
+                            )?),
+                        },
+                    );
+                }
+            }
+
+            {
+                let pattern_builtin_texture_audio = Regex::new(r"^builtin_texture_audio_(?P<field>\w+)$").unwrap();
                 let pattern_field_previous = Regex::new(r"^.*_previous$").unwrap();
                 let param_names = params.keys().cloned().collect::<Vec<_>>();
 
                 for param_name in &param_names {
-                    let captures = if let Some(captures) = pattern_builtin_texture_fft.captures(&param_name) {
+                    let captures = if let Some(captures) = pattern_builtin_texture_audio.captures(&param_name) {
                         captures
                     } else {
                         continue;
@@ -77,7 +147,7 @@ impl EffectParamsCustom {
                     bound_params.push(
                         Indexed {
                             index: param_index,
-                            inner: Box::new(EffectParamCustomFFT::new(
+                            inner: Box::new(EffectParamCustomAudioWaveform::new(
                                 param.downcast().unwrap(),
                                 param_previous.map(|param_previous| param_previous.downcast().unwrap()),
                                 field_name,
@@ -88,6 +158,192 @@ impl EffectParamsCustom {
                     );
                 }
             }
+
+            {
+                let pattern_builtin_beat = Regex::new(r"^builtin_beat_(?:(?:time|strength|bpm)_)?(?P<field>\w+)$").unwrap();
+                let mut field_names = params.keys()
+                    .filter_map(|param_name| pattern_builtin_beat.captures(param_name))
+                    .map(|captures| captures.name("field").unwrap().as_str().to_string())
+                    .collect::<Vec<_>>();
+
+                field_names.sort_unstable();
+                field_names.dedup();
+
+                for field_name in &field_names {
+                    let mut take_param = |prefix: &str| -> Result<Option<Indexed<GraphicsContextDependentEnabled<'a, GraphicsEffectParam>>>, Cow<'static, str>> {
+                        let param_name = format!("{}{}", prefix, field_name);
+                        let param = params.remove(&param_name);
+
+                        if let Some(ref param) = param.as_ref() {
+                            if param.param_type() != Float {
+                                throw!(format!("Builtin field `{}` must be of type `{}`", param_name, "float"));
+                            }
+                        }
+
+                        Ok(param)
+                    };
+                    let param_trigger = take_param("builtin_beat_")?;
+                    let param_time = take_param("builtin_beat_time_")?;
+                    let param_strength = take_param("builtin_beat_strength_")?;
+                    let param_bpm = take_param("builtin_beat_bpm_")?;
+                    let param_index = [&param_trigger, &param_time, &param_strength, &param_bpm].iter()
+                        .filter_map(|param| param.as_ref().map(|param| param.index))
+                        .min()
+                        .unwrap();
+                    let downcast = |param: Option<Indexed<GraphicsContextDependentEnabled<'a, GraphicsEffectParam>>>| {
+                        param.map(|param| param.into_inner().downcast().unwrap())
+                    };
+
+                    bound_params.push(
+                        Indexed {
+                            index: param_index,
+                            inner: Box::new(EffectParamCustomBeat::new(
+                                downcast(param_trigger),
+                                downcast(param_time),
+                                downcast(param_strength),
+                                downcast(param_bpm),
+                                field_name,
+                                settings,
+                                preprocess_result,
+                            )?),
+                        },
+                    );
+                }
+            }
+
+            {
+                let pattern_builtin_audio_level = Regex::new(r"^builtin_audio_(?:rms|peak)(?:_db)?_(?P<field>\w+)$").unwrap();
+                let mut field_names = params.keys()
+                    .filter_map(|param_name| pattern_builtin_audio_level.captures(param_name))
+                    .map(|captures| captures.name("field").unwrap().as_str().to_string())
+                    .collect::<Vec<_>>();
+
+                field_names.sort_unstable();
+                field_names.dedup();
+
+                for field_name in &field_names {
+                    let mut take_param = |prefix: &str| -> Result<Option<Indexed<GraphicsContextDependentEnabled<'a, GraphicsEffectParam>>>, Cow<'static, str>> {
+                        let param_name = format!("{}{}", prefix, field_name);
+                        let param = params.remove(&param_name);
+
+                        if let Some(ref param) = param.as_ref() {
+                            if param.param_type() != Float {
+                                throw!(format!("Builtin field `{}` must be of type `{}`", param_name, "float"));
+                            }
+                        }
+
+                        Ok(param)
+                    };
+                    let param_rms = take_param("builtin_audio_rms_")?;
+                    let param_rms_db = take_param("builtin_audio_rms_db_")?;
+                    let param_peak = take_param("builtin_audio_peak_")?;
+                    let param_peak_db = take_param("builtin_audio_peak_db_")?;
+                    let param_index = [&param_rms, &param_rms_db, &param_peak, &param_peak_db].iter()
+                        .filter_map(|param| param.as_ref().map(|param| param.index))
+                        .min()
+                        .unwrap();
+                    let downcast = |param: Option<Indexed<GraphicsContextDependentEnabled<'a, GraphicsEffectParam>>>| {
+                        param.map(|param| param.into_inner().downcast().unwrap())
+                    };
+
+                    bound_params.push(
+                        Indexed {
+                            index: param_index,
+                            inner: Box::new(EffectParamCustomAudioLevel::new(
+                                downcast(param_rms),
+                                downcast(param_rms_db),
+                                downcast(param_peak),
+                                downcast(param_peak_db),
+                                field_name,
+                                settings,
+                                preprocess_result,
+                            )?),
+                        },
+                    );
+                }
+            }
+
+            {
+                let pattern_builtin_audio_pitch = Regex::new(r"^builtin_audio_pitch_(?:(?:confidence|note)_)?(?P<field>\w+)$").unwrap();
+                let mut field_names = params.keys()
+                    .filter_map(|param_name| pattern_builtin_audio_pitch.captures(param_name))
+                    .map(|captures| captures.name("field").unwrap().as_str().to_string())
+                    .collect::<Vec<_>>();
+
+                field_names.sort_unstable();
+                field_names.dedup();
+
+                for field_name in &field_names {
+                    let mut take_param = |prefix: &str| -> Result<Option<Indexed<GraphicsContextDependentEnabled<'a, GraphicsEffectParam>>>, Cow<'static, str>> {
+                        let param_name = format!("{}{}", prefix, field_name);
+                        let param = params.remove(&param_name);
+
+                        if let Some(ref param) = param.as_ref() {
+                            if param.param_type() != Float {
+                                throw!(format!("Builtin field `{}` must be of type `{}`", param_name, "float"));
+                            }
+                        }
+
+                        Ok(param)
+                    };
+                    let param_frequency = take_param("builtin_audio_pitch_")?;
+                    let param_confidence = take_param("builtin_audio_pitch_confidence_")?;
+                    let param_note = take_param("builtin_audio_pitch_note_")?;
+                    let param_index = [&param_frequency, &param_confidence, &param_note].iter()
+                        .filter_map(|param| param.as_ref().map(|param| param.index))
+                        .min()
+                        .unwrap();
+                    let downcast = |param: Option<Indexed<GraphicsContextDependentEnabled<'a, GraphicsEffectParam>>>| {
+                        param.map(|param| param.into_inner().downcast().unwrap())
+                    };
+
+                    bound_params.push(
+                        Indexed {
+                            index: param_index,
+                            inner: Box::new(EffectParamCustomAudioPitch::new(
+                                downcast(param_frequency),
+                                downcast(param_confidence),
+                                downcast(param_note),
+                                field_name,
+                                settings,
+                                preprocess_result,
+                            )?),
+                        },
+                    );
+                }
+            }
+
+            {
+                let pattern_builtin_texture_source = Regex::new(r"^builtin_texture_source_(?P<field>\w+)$").unwrap();
+                let param_names = params.keys().cloned().collect::<Vec<_>>();
+
+                for param_name in &param_names {
+                    let captures = if let Some(captures) = pattern_builtin_texture_source.captures(&param_name) {
+                        captures
+                    } else {
+                        continue;
+                    };
+                    let field_name = captures.name("field").unwrap().as_str();
+                    let (param_index, param) = params.remove(param_name).unwrap().into_tuple();
+
+                    if param.param_type() != Texture {
+                        throw!(format!("Builtin field `{}` must be of type `{}`", field_name, "texture2d"));
+                    }
+
+                    bound_params.push(
+                        Indexed {
+                            index: param_index,
+                            inner: Box::new(EffectParamCustomSource::new(
+                                param.downcast().unwrap(),
+                                field_name,
+                                filter,
+                                settings,
+                                preprocess_result,
+                            )?),
+                        },
+                    );
+                }
+            }
         };
 
         result.map_err(|err| {
@@ -147,11 +403,35 @@ impl EffectParamsCustom {
                 EffectParamCustomColor::new(param.downcast().unwrap(), &param_name, settings, preprocess_result)
                     .map(|param| Box::new(param) as Box<dyn BindableProperty>)
             }).transpose()?,
-            Vec2 | Vec3 | IVec2 | IVec3 | IVec4 | Mat4 => {
-                throw!("Multi-component types as effect params are not yet supported.");
-            },
+            Vec2  => param.map(|param| {
+                EffectParamCustomVec2::new(param.downcast().unwrap(), &param_name, settings, preprocess_result)
+                    .map(|param| Box::new(param) as Box<dyn BindableProperty>)
+            }).transpose()?,
+            Vec3  => param.map(|param| {
+                EffectParamCustomVec3::new(param.downcast().unwrap(), &param_name, settings, preprocess_result)
+                    .map(|param| Box::new(param) as Box<dyn BindableProperty>)
+            }).transpose()?,
+            IVec2  => param.map(|param| {
+                EffectParamCustomIVec2::new(param.downcast().unwrap(), &param_name, settings, preprocess_result)
+                    .map(|param| Box::new(param) as Box<dyn BindableProperty>)
+            }).transpose()?,
+            IVec3  => param.map(|param| {
+                EffectParamCustomIVec3::new(param.downcast().unwrap(), &param_name, settings, preprocess_result)
+                    .map(|param| Box::new(param) as Box<dyn BindableProperty>)
+            }).transpose()?,
+            IVec4  => param.map(|param| {
+                EffectParamCustomIVec4::new(param.downcast().unwrap(), &param_name, settings, preprocess_result)
+                    .map(|param| Box::new(param) as Box<dyn BindableProperty>)
+            }).transpose()?,
+            Mat4  => param.map(|param| {
+                EffectParamCustomMat4::new(param.downcast().unwrap(), &param_name, settings, preprocess_result)
+                    .map(|param| Box::new(param) as Box<dyn BindableProperty>)
+            }).transpose()?,
             String => throw!("Strings as effect params are not yet supported."),
-            Texture => throw!("Textures as effect params are not yet supported."),
+            Texture  => param.map(|param| {
+                EffectParamCustomTexture::new(param.downcast().unwrap(), &param_name, settings, preprocess_result)
+                    .map(|param| Box::new(param) as Box<dyn BindableProperty>)
+            }).transpose()?,
         };
 
         bound_params.push(bindable);
@@ -163,6 +443,11 @@ impl EffectParamsCustom {
         self.params.iter_mut().for_each(|param| param.reload_settings(settings));
     }
 
+    /// The first error which occurred while loading a resource of a property, if any.
+    pub fn load_error(&self) -> Option<&str> {
+        self.params.iter().find_map(|param| param.load_error())
+    }
+
     pub fn prepare_values(&mut self) {
         self.params.iter_mut().for_each(|param| param.prepare_values());
     }
@@ -171,6 +456,25 @@ impl EffectParamsCustom {
         self.params.iter_mut().for_each(|param| param.stage_value(graphics_context));
     }
 
+    pub fn render_textures(&mut self, graphics_context: &GraphicsContext) {
+        self.params.iter_mut().for_each(|param| param.render_textures(graphics_context));
+    }
+
+    /// Registers the hotkeys on the filter, keeping the key combinations of the hotkeys of the `previous` effect.
+    pub fn register_hotkeys(&mut self, filter: *mut obs_source_t, previous: Option<&mut EffectParamsCustom>) {
+        let previous_hotkeys = previous
+            .map(|previous| previous.params.iter_mut().filter_map(|param| param.hotkeys_mut()).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        for hotkeys in self.params.iter_mut().filter_map(|param| param.hotkeys_mut()) {
+            hotkeys.register(filter, &previous_hotkeys);
+        }
+    }
+
+    pub fn apply_hotkeys(&mut self, settings: &mut SettingsContext) {
+        self.params.iter_mut().for_each(|param| param.apply_hotkeys(settings));
+    }
+
     pub fn assign_values(&mut self, graphics_context: &FilterContext) {
         self.params.iter_mut().for_each(|param| param.assign_value(graphics_context));
     }
@@ -194,10 +498,58 @@ impl EffectParamsCustom {
                 Ok(param) => return param.enable_and_drop(graphics_context),
                 Err(param) => param,
             };
+            param = match param.downcast::<EffectParamCustomTexture>() {
+                Ok(param) => return param.enable_and_drop(graphics_context),
+                Err(param) => param,
+            };
+            param = match param.downcast::<EffectParamCustomVec2>() {
+                Ok(param) => return param.enable_and_drop(graphics_context),
+                Err(param) => param,
+            };
+            param = match param.downcast::<EffectParamCustomVec3>() {
+                Ok(param) => return param.enable_and_drop(graphics_context),
+                Err(param) => param,
+            };
+            param = match param.downcast::<EffectParamCustomIVec2>() {
+                Ok(param) => return param.enable_and_drop(graphics_context),
+                Err(param) => param,
+            };
+            param = match param.downcast::<EffectParamCustomIVec3>() {
+                Ok(param) => return param.enable_and_drop(graphics_context),
+                Err(param) => param,
+            };
+            param = match param.downcast::<EffectParamCustomIVec4>() {
+                Ok(param) => return param.enable_and_drop(graphics_context),
+                Err(param) => param,
+            };
+            param = match param.downcast::<EffectParamCustomMat4>() {
+                Ok(param) => return param.enable_and_drop(graphics_context),
+                Err(param) => param,
+            };
             param = match param.downcast::<EffectParamCustomFFT>() {
                 Ok(param) => return param.enable_and_drop(graphics_context),
                 Err(param) => param,
             };
+            param = match param.downcast::<EffectParamCustomAudioWaveform>() {
+                Ok(param) => return param.enable_and_drop(graphics_context),
+                Err(param) => param,
+            };
+            param = match param.downcast::<EffectParamCustomAudioLevel>() {
+                Ok(param) => return param.enable_and_drop(graphics_context),
+                Err(param) => param,
+            };
+            param = match param.downcast::<EffectParamCustomAudioPitch>() {
+                Ok(param) => return param.enable_and_drop(graphics_context),
+                Err(param) => param,
+            };
+            param = match param.downcast::<EffectParamCustomBeat>() {
+                Ok(param) => return param.enable_and_drop(graphics_context),
+                Err(param) => param,
+            };
+            param = match param.downcast::<EffectParamCustomSource>() {
+                Ok(param) => return param.enable_and_drop(graphics_context),
+                Err(param) => param,
+            };
             panic!("No registered downcast to `enable_and_drop` a `Box<dyn BindableProperty>`. This is an implementation error.");
         });
     }
@@ -217,6 +569,11 @@ pub struct EffectParams {
     pub elapsed_time_since_enabled: EffectParamFloat,
     pub elapsed_time_since_enabled_previous: EffectParamFloat,
     pub uv_size: EffectParamIVec2,
+    pub date: Option<EffectParamVec4>,
+    pub pass_index: EffectParamInt,
+    pub previous_output: Option<EffectParamRawTexture>,
+    /// Outputs of intermediate passes, in the order of `PreparedEffect::passes`.
+    pub passes: Vec<EffectParamRawTexture>,
     pub custom: EffectParamsCustom,
 }
 
@@ -235,9 +592,21 @@ impl EffectParams {
         self.elapsed_time_since_enabled.stage_value(graphics_context);
         self.elapsed_time_since_enabled_previous.stage_value(graphics_context);
         self.uv_size.stage_value(graphics_context);
+        if let Some(date) = self.date.as_mut() {
+            date.stage_value(graphics_context);
+        }
+        self.pass_index.stage_value(graphics_context);
         self.custom.stage_values(graphics_context);
     }
 
+    pub fn render_textures(&mut self, graphics_context: &GraphicsContext) {
+        self.custom.render_textures(graphics_context);
+    }
+
+    pub fn apply_hotkeys(&mut self, settings: &mut SettingsContext) {
+        self.custom.apply_hotkeys(settings);
+    }
+
     pub fn assign_values(&mut self, graphics_context: &FilterContext) {
         self.frame.assign_value(graphics_context);
         self.framerate.assign_value(graphics_context);
@@ -248,6 +617,14 @@ impl EffectParams {
         self.elapsed_time_since_enabled.assign_value(graphics_context);
         self.elapsed_time_since_enabled_previous.assign_value(graphics_context);
         self.uv_size.assign_value(graphics_context);
+        if let Some(date) = self.date.as_mut() {
+            date.assign_value(graphics_context);
+        }
+        self.pass_index.assign_value(graphics_context);
+        if let Some(previous_output) = self.previous_output.as_mut() {
+            previous_output.assign_value(graphics_context);
+        }
+        self.passes.iter_mut().for_each(|pass| pass.assign_value(graphics_context));
         self.custom.assign_values(graphics_context);
     }
 
@@ -261,6 +638,14 @@ impl EffectParams {
         self.elapsed_time_since_enabled.enable_and_drop(graphics_context);
         self.elapsed_time_since_enabled_previous.enable_and_drop(graphics_context);
         self.uv_size.enable_and_drop(graphics_context);
+        if let Some(date) = self.date {
+            date.enable_and_drop(graphics_context);
+        }
+        self.pass_index.enable_and_drop(graphics_context);
+        if let Some(previous_output) = self.previous_output {
+            previous_output.enable_and_drop(graphics_context);
+        }
+        self.passes.into_iter().for_each(|pass| pass.enable_and_drop(graphics_context));
         self.custom.enable_and_drop(graphics_context);
     }
 
@@ -269,39 +654,195 @@ impl EffectParams {
     }
 }
 
+/// The texture a pass is rendered into.
+pub enum PassRenderTarget {
+    Single(RenderTarget),
+    /// Keeps the output of the previous frame, for persistent passes.
+    Persistent(FeedbackRenderTargets),
+}
+
+/// An intermediate render pass, rendered into a texture before the final `render` pass.
+pub struct RenderPass {
+    pub descriptor: PassDescriptor,
+    pub render_target: PassRenderTarget,
+}
+
+impl RenderPass {
+    pub fn new(descriptor: PassDescriptor, graphics_context: &GraphicsContext) -> Self {
+        let render_target = if descriptor.persistent {
+            PassRenderTarget::Persistent(FeedbackRenderTargets::new(graphics_context))
+        } else {
+            PassRenderTarget::Single(RenderTarget::new(graphics_context))
+        };
+
+        Self {
+            descriptor,
+            render_target,
+        }
+    }
+
+    pub fn get_dimensions(&self, source_dimensions: [u32; 2]) -> [u32; 2] {
+        let scale = |dimension: u32| ((dimension as f32 * self.descriptor.scale).round() as u32).max(1);
+
+        [scale(source_dimensions[0]), scale(source_dimensions[1])]
+    }
+
+    /// Returns the output available before the pass is rendered in the current frame,
+    /// which is the output of the previous frame for persistent passes, or null otherwise.
+    pub fn prepare(&mut self, source_dimensions: [u32; 2]) -> *mut gs_texture_t {
+        let dimensions = self.get_dimensions(source_dimensions);
+
+        match &mut self.render_target {
+            PassRenderTarget::Single(_) => std::ptr::null_mut(),
+            PassRenderTarget::Persistent(feedback) => {
+                feedback.prepare(dimensions);
+                feedback.texture_previous()
+            },
+        }
+    }
+
+    pub fn render(
+        &mut self,
+        dimensions: [u32; 2],
+        graphics_context: &GraphicsContext,
+        render: impl FnOnce(),
+    ) -> bool {
+        match &mut self.render_target {
+            PassRenderTarget::Single(render_target) => render_target.render(dimensions, graphics_context, render),
+            PassRenderTarget::Persistent(feedback) => feedback.render_offscreen(dimensions, graphics_context, render),
+        }
+    }
+
+    /// The output of the most recent render.
+    pub fn texture(&self) -> *mut gs_texture_t {
+        match &self.render_target {
+            PassRenderTarget::Single(render_target) => render_target.texture(),
+            PassRenderTarget::Persistent(feedback) => feedback.texture_previous(),
+        }
+    }
+
+    pub fn destroy(self, graphics_context: &GraphicsContext) {
+        match self.render_target {
+            PassRenderTarget::Single(render_target) => render_target.destroy(graphics_context),
+            PassRenderTarget::Persistent(feedback) => feedback.destroy(graphics_context),
+        }
+    }
+}
+
 pub struct PreparedEffect {
     pub effect: GraphicsContextDependentDisabled<GraphicsEffect>,
     pub shader_source: String,
+    pub includes: Vec<IncludedSource>,
     pub params: EffectParams,
+    /// Holds the output of the previous frame, if `builtin_texture_previous_output` is used.
+    pub feedback: Option<FeedbackRenderTargets>,
+    pub passes: Vec<RenderPass>,
 }
 
 impl PreparedEffect {
     pub fn enable_and_drop(self, graphics_context: &GraphicsContext) {
         self.effect.enable(graphics_context);
         self.params.enable_and_drop(graphics_context);
+        if let Some(feedback) = self.feedback {
+            feedback.destroy(graphics_context);
+        }
+        self.passes.into_iter().for_each(|pass| pass.destroy(graphics_context));
     }
 
     pub fn add_properties(&self, properties: &mut Properties) {
         self.params.add_properties(properties);
     }
 
-    pub fn create_effect<'a>(shader_path: &PathBuf, shader_source: &str, graphics_context: &'a GraphicsContext) -> Result<(GraphicsContextDependentEnabled<'a, GraphicsEffect>, PreprocessResult), Cow<'static, str>> {
+    /// Returns `true`, if any of the included files was changed or removed since the effect was created.
+    pub fn includes_changed(&self) -> bool {
+        self.includes.iter().any(|included| {
+            std::fs::read_to_string(&included.path)
+                .map(|source| source != included.source)
+                .unwrap_or(true)
+        })
+    }
+
+    /// Rewrites the locations in compiler error messages, which refer to lines of the generated
+    /// effect, to refer to the lines of the original shader files, in the form of `file:line:column`.
+    fn map_error_locations(
+        error: &str,
+        shader_path: &str,
+        template: &str,
+        template_path: &str,
+        source_map: &SourceMap,
+    ) -> String {
+        // The number of lines of the template preceding the shader.
+        let template_offset = template.find("__SHADER__")
+            .map(|index| template[..index].matches('\n').count())
+            .unwrap_or(0);
+        // Matches on locations in the following forms:
+        // <shader_path> (<line>, <column>)
+        // <shader_path>(<line>,<column>-<column_end>)
+        let pattern = Regex::new(&format!(
+            r"{}\s*\((?P<line>\d+),\s*(?P<column>\d+)(?:-\d+)?\)",
+            regex::escape(shader_path),
+        )).unwrap();
+
+        pattern.replace_all(error, |captures: &Captures| {
+            let line = captures.name("line").unwrap().as_str().parse::<usize>().unwrap_or(0);
+            let column = captures.name("column").unwrap().as_str();
+
+            if line <= template_offset {
+                format!("{}:{}:{}", template_path, line, column)
+            } else if line <= template_offset + source_map.len() {
+                let (path, line) = source_map.resolve(line - template_offset).unwrap();
+
+                format!("{}:{}:{}", path.display(), line, column)
+            } else {
+                // The `__SHADER__` line is replaced by the shader.
+                format!("{}:{}:{}", template_path, line - source_map.len(), column)
+            }
+        }).into_owned()
+    }
+
+    pub fn create_effect<'a>(shader_path: &PathBuf, shader_source: &str, line_offset: usize, graphics_context: &'a GraphicsContext) -> Result<(GraphicsContextDependentEnabled<'a, GraphicsEffect>, PreprocessResult), Cow<'static, str>> {
         const EFFECT_SOURCE_TEMPLATE: &'static str = include_str!("../effect_template.effect");
+        const EFFECT_PATH_TEMPLATE: &'static str = "effect_template.effect";
+        const EFFECT_SOURCE_TEMPLATE_SHADERTOY: &'static str = include_str!("../effect_template_shadertoy.effect");
+        const EFFECT_PATH_TEMPLATE_SHADERTOY: &'static str = "effect_template_shadertoy.effect";
+        const EFFECT_SOURCE_TEMPLATE_ISF: &'static str = include_str!("../effect_template_isf.effect");
+        const EFFECT_PATH_TEMPLATE_ISF: &'static str = "effect_template_isf.effect";
+
+        let (preprocess_result, shader_source) = preprocess(shader_source, shader_path, line_offset)?;
+        let (template_source, template_path) = match preprocess_result.dialect() {
+            ShaderDialect::Native => (EFFECT_SOURCE_TEMPLATE, EFFECT_PATH_TEMPLATE),
+            ShaderDialect::Shadertoy => (EFFECT_SOURCE_TEMPLATE_SHADERTOY, EFFECT_PATH_TEMPLATE_SHADERTOY),
+            ShaderDialect::Isf => (EFFECT_SOURCE_TEMPLATE_ISF, EFFECT_PATH_TEMPLATE_ISF),
+        };
 
-        let (preprocess_result, effect_source) = {
+        let effect_source = {
+            // Generated on a single line each, so that the line numbers of the template are preserved.
+            let pass_uniforms = preprocess_result.passes().iter()
+                .map(|pass| format!("uniform texture2d builtin_texture_pass_{};", pass.name))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let pass_dispatch = preprocess_result.passes().iter()
+                .enumerate()
+                .map(|(index, pass)| {
+                    format!("if (builtin_pass_index == {}) return render_pass_{}(v_in.uv);", index, pass.name)
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            let effect_source = template_source
+                .replace("__PASS_UNIFORMS__", &pass_uniforms)
+                .replace("__PASS_DISPATCH__", &pass_dispatch);
             let pattern = Regex::new(r"(?P<shader>__SHADER__)").unwrap();
-            let effect_source = pattern.replace_all(EFFECT_SOURCE_TEMPLATE, shader_source);
+            let effect_source = pattern.replace_all(&effect_source, NoExpand(&shader_source));
 
-            let (preprocess_result, effect_source) = preprocess(&effect_source);
-
-            (preprocess_result, effect_source.into_owned())
+            effect_source.into_owned()
         };
 
-        let shader_path_c = CString::new(
-            shader_path.to_str().ok_or_else(|| {
-                "Specified shader path is not a valid UTF-8 string."
-            })?
-        ).map_err(|_| "Shader path cannot be converted to a C string.")?;
+        let shader_path_str = shader_path.to_str().ok_or_else(|| {
+            "Specified shader path is not a valid UTF-8 string."
+        })?;
+        let shader_path_c = CString::new(shader_path_str)
+            .map_err(|_| "Shader path cannot be converted to a C string.")?;
         let effect_source_c = CString::new(effect_source.clone())
             .map_err(|_| "Shader contents cannot be converted to a C string.")?;
 
@@ -314,10 +855,18 @@ impl PreparedEffect {
             );
 
             result.map_err(|err| {
+                let map_error_locations = |error: &str| Self::map_error_locations(
+                    error,
+                    shader_path_str,
+                    template_source,
+                    template_path,
+                    preprocess_result.source_map(),
+                );
+
                 if let Some(err) = err {
-                    Cow::Owned(format!("Could not create the effect due to the following error: {}", err))
+                    Cow::Owned(format!("Could not create the effect due to the following error: {}", map_error_locations(&err.to_string())))
                 } else {
-                    Cow::Owned(format!("Could not create the effect due to the following error:\n{}", capture.to_string()))
+                    Cow::Owned(format!("Could not create the effect due to the following error:\n{}", map_error_locations(&capture.to_string())))
                 }
             })
         }?;
@@ -325,3 +874,26 @@ impl PreparedEffect {
         Ok((effect, preprocess_result))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_locations_are_mapped_to_the_original_files() {
+        let (preprocess_result, _) = preprocess("x\ny\n", std::path::Path::new("shader.effect"), 0).unwrap();
+        let error = "effect(3, 5): error X3000\neffect (1,2): warning\neffect(6,1-4): error X3004";
+        let mapped = PreparedEffect::map_error_locations(
+            error,
+            "effect",
+            "a\nb\n__SHADER__\nc\n",
+            "template.effect",
+            preprocess_result.source_map(),
+        );
+
+        assert_eq!(
+            mapped,
+            "shader.effect:1:5: error X3000\ntemplate.effect:1:2: warning\ntemplate.effect:4:1: error X3004",
+        );
+    }
+}
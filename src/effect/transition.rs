@@ -0,0 +1,252 @@
+use std::borrow::Cow;
+use std::time::Instant;
+use crate::*;
+
+/// The progress of a transition over time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    pub const NAMES: &'static [&'static str] = &[
+        "Linear", "EaseIn", "EaseOut", "EaseInOut",
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        use Easing::*;
+
+        Some(match name {
+            "Linear" => Linear,
+            "EaseIn" => EaseIn,
+            "EaseOut" => EaseOut,
+            "EaseInOut" => EaseInOut,
+            _ => return None,
+        })
+    }
+
+    /// Maps the elapsed fraction of the transition to the interpolation factor, both from 0 to 1.
+    pub fn apply(self, t: f64) -> f64 {
+        use Easing::*;
+
+        match self {
+            Linear => t,
+            EaseIn => t * t * t,
+            EaseOut => 1.0 - (1.0 - t).powi(3),
+            EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            },
+        }
+    }
+}
+
+/// Interpolates the components of a value, whenever its setting is changed,
+/// enabled using `<NAME>__transition_duration` and `<NAME>__easing`.
+pub struct Transition {
+    /// In seconds.
+    duration: f64,
+    easing: Easing,
+    from: Vec<f64>,
+    to: Vec<f64>,
+    current: Vec<f64>,
+    started_at: Option<Instant>,
+}
+
+impl Transition {
+    /// Returns `None`, unless a positive transition duration is specified in the shader source code.
+    pub fn from_identifier(
+        identifier: &str,
+        initial_value: Vec<f64>,
+        preprocess_result: &PreprocessResult,
+    ) -> Result<Option<Self>, Cow<'static, str>> {
+        let duration = preprocess_result.parse::<f64>(&format!("{}__transition_duration", identifier))
+            .transpose()?
+            .unwrap_or(0.0);
+        let easing_name = preprocess_result.parse::<String>(&format!("{}__easing", identifier))
+            .transpose()?;
+        let easing = match easing_name {
+            Some(easing_name) => Easing::from_name(&easing_name)
+                .ok_or_else(|| format!("Invalid easing `{}` of property `{}`, expected one of: {}", easing_name, identifier, Easing::NAMES.join(", ")))?,
+            None => Easing::EaseInOut,
+        };
+
+        if !duration.is_finite() || duration < 0.0 {
+            throw!(format!("The transition duration of property `{}` must be a non-negative number of seconds.", identifier));
+        }
+
+        if duration == 0.0 {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            duration,
+            easing,
+            from: initial_value.clone(),
+            to: initial_value.clone(),
+            current: initial_value,
+            started_at: None,
+        }))
+    }
+
+    /// Starts a transition from the current value, unless the target value is unchanged.
+    pub fn set_target(&mut self, target: Vec<f64>) {
+        if target == self.to {
+            return;
+        }
+
+        self.from = self.current.clone();
+        self.to = target;
+        self.started_at = Some(Instant::now());
+    }
+
+    /// Returns the interpolated value while transitioning, including the final value once the transition ends.
+    pub fn update(&mut self) -> Option<&[f64]> {
+        let started_at = self.started_at?;
+        let t = started_at.elapsed().as_secs_f64() / self.duration;
+
+        if t >= 1.0 {
+            self.started_at = None;
+            self.current = self.to.clone();
+        } else {
+            let factor = self.easing.apply(t);
+
+            self.current = self.from.iter()
+                .zip(&self.to)
+                .map(|(from, to)| from + (to - from) * factor)
+                .collect();
+        }
+
+        Some(&self.current)
+    }
+}
+
+fn srgb_to_linear(component: f64) -> f64 {
+    if component <= 0.04045 {
+        component / 12.92
+    } else {
+        ((component + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(component: f64) -> f64 {
+    if component <= 0.0031308 {
+        component * 12.92
+    } else {
+        1.055 * component.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Converts a non-linear sRGB color with alpha to the perceptual Oklab color space, keeping the alpha.
+pub fn srgba_to_oklab(rgba: [f32; 4]) -> Vec<f64> {
+    let [r, g, b] = [rgba[0], rgba[1], rgba[2]].map(|component| srgb_to_linear(component as f64));
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+    vec![
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        rgba[3] as f64,
+    ]
+}
+
+/// The inverse of `srgba_to_oklab`.
+pub fn oklab_to_srgba(lab: &[f64]) -> [f32; 4] {
+    let l = (lab[0] + 0.3963377774 * lab[1] + 0.2158037573 * lab[2]).powi(3);
+    let m = (lab[0] - 0.1055613458 * lab[1] - 0.0638541728 * lab[2]).powi(3);
+    let s = (lab[0] - 0.0894841775 * lab[1] - 1.2914855480 * lab[2]).powi(3);
+    let rgb = [
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+    ];
+    let [r, g, b] = rgb.map(|component| linear_to_srgb(component).clamp(0.0, 1.0) as f32);
+
+    [r, g, b, lab[3].clamp(0.0, 1.0) as f32]
+}
+
+/// A component of a value, which may be interpolated.
+pub trait TransitionComponent {
+    fn to_f64(&self) -> f64;
+    fn from_f64(value: f64) -> Self;
+}
+
+impl TransitionComponent for f64 {
+    fn to_f64(&self) -> f64 {
+        *self
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+impl TransitionComponent for i32 {
+    fn to_f64(&self) -> f64 {
+        *self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value.round() as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn easings_start_at_zero_and_end_at_one() {
+        for name in Easing::NAMES {
+            let easing = Easing::from_name(name).unwrap();
+            let samples = (0..=100).map(|step| easing.apply(step as f64 / 100.0)).collect::<Vec<_>>();
+
+            assert!(samples[0].abs() < 1e-12, "{}", name);
+            assert!((samples[100] - 1.0).abs() < 1e-12, "{}", name);
+            assert!(samples.windows(2).all(|pair| pair[0] <= pair[1]), "{} is not monotonic", name);
+        }
+
+        assert_eq!(Easing::Linear.apply(0.25), 0.25);
+        assert_eq!(Easing::EaseIn.apply(0.5), 0.125);
+        assert_eq!(Easing::EaseOut.apply(0.5), 0.875);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert!(Easing::from_name("Bounce").is_none());
+    }
+
+    #[test]
+    fn oklab_round_trip_preserves_colors() {
+        let colors = [
+            [0.0, 0.0, 0.0, 1.0],
+            [1.0, 1.0, 1.0, 0.5],
+            [1.0, 0.0, 0.0, 1.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 1.0],
+            [0.2, 0.5, 0.8, 0.25],
+        ];
+
+        for color in colors.iter() {
+            let round_trip = oklab_to_srgba(&srgba_to_oklab(*color));
+
+            for (original, converted) in color.iter().zip(round_trip.iter()) {
+                assert!((original - converted).abs() < 1e-4, "{:?} became {:?}", color, round_trip);
+            }
+        }
+    }
+
+    #[test]
+    fn oklab_maps_white_to_full_lightness_without_chroma() {
+        let lab = srgba_to_oklab([1.0, 1.0, 1.0, 1.0]);
+
+        assert!((lab[0] - 1.0).abs() < 1e-4);
+        assert!(lab[1].abs() < 1e-4);
+        assert!(lab[2].abs() < 1e-4);
+    }
+}
@@ -0,0 +1,178 @@
+use std::ffi::CString;
+use std::os::raw::c_void;
+use std::sync::{Arc, Mutex};
+use obs_wrapper::obs_sys::{
+    obs_hotkey_id, obs_hotkey_t, obs_source_t, obs_hotkey_register_source, obs_hotkey_unregister,
+    obs_hotkey_save, obs_hotkey_load, obs_data_array_release,
+};
+use crate::*;
+
+/// The effect of a hotkey on the value of a uniform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HotkeyAction {
+    /// Inverts a `bool` when pressed.
+    Toggle,
+    /// Inverts a `bool` while held.
+    Hold,
+    /// Selects the next value of an `int`.
+    Next,
+    /// Selects the previous value of an `int`.
+    Previous,
+}
+
+impl HotkeyAction {
+    pub fn name(self) -> &'static str {
+        match self {
+            HotkeyAction::Toggle => "toggle",
+            HotkeyAction::Hold => "hold",
+            HotkeyAction::Next => "next",
+            HotkeyAction::Previous => "previous",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            HotkeyAction::Toggle => "Toggle",
+            HotkeyAction::Hold => "Hold",
+            HotkeyAction::Next => "Next",
+            HotkeyAction::Previous => "Previous",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HotkeyEvent {
+    pub action: HotkeyAction,
+    pub pressed: bool,
+}
+
+/// Events are queued by the hotkey thread and applied in `video_tick`, where the settings are accessible.
+pub type HotkeyEvents = Arc<Mutex<Vec<HotkeyEvent>>>;
+
+struct HotkeyCallbackData {
+    action: HotkeyAction,
+    events: HotkeyEvents,
+}
+
+unsafe extern "C" fn hotkey_callback(
+    data: *mut c_void,
+    _id: obs_hotkey_id,
+    _hotkey: *mut obs_hotkey_t,
+    pressed: bool,
+) {
+    let data = &*(data as *const HotkeyCallbackData);
+
+    data.events.lock().unwrap().push(HotkeyEvent {
+        action: data.action,
+        pressed,
+    });
+}
+
+/// A hotkey registered on the filter, unregistered on drop.
+struct Hotkey {
+    name: String,
+    id: obs_hotkey_id,
+    data: *mut HotkeyCallbackData,
+}
+
+// The callback data is only accessed by OBS between registration and unregistration.
+unsafe impl Send for Hotkey {}
+unsafe impl Sync for Hotkey {}
+
+impl Hotkey {
+    /// Registers the hotkey on the filter, so that OBS lists it under the filter and saves its key combinations with it.
+    fn register(filter: *mut obs_source_t, name: String, description: &str, action: HotkeyAction, events: HotkeyEvents) -> Self {
+        let name_c = CString::new(name.clone()).unwrap_or_default();
+        let description = CString::new(description).unwrap_or_default();
+        let data = Box::into_raw(Box::new(HotkeyCallbackData { action, events }));
+        let id = unsafe {
+            obs_hotkey_register_source(filter, name_c.as_ptr(), description.as_ptr(), Some(hotkey_callback), data as *mut c_void)
+        };
+
+        Self { name, id, data }
+    }
+
+    /// Binds the key combinations of another hotkey to this one.
+    fn copy_bindings(&self, other: &Hotkey) {
+        unsafe {
+            let bindings = obs_hotkey_save(other.id);
+
+            obs_hotkey_load(self.id, bindings);
+            obs_data_array_release(bindings);
+        }
+    }
+}
+
+impl Drop for Hotkey {
+    fn drop(&mut self) {
+        unsafe {
+            obs_hotkey_unregister(self.id);
+            drop(Box::from_raw(self.data));
+        }
+    }
+}
+
+/// The hotkeys of a single uniform, enabled using `<NAME>__hotkey true`.
+/// They are registered on the filter once the effect is created, using `register`.
+pub struct ParamHotkeys {
+    identifier: String,
+    description: String,
+    actions: Vec<HotkeyAction>,
+    hotkeys: Vec<Hotkey>,
+    events: HotkeyEvents,
+}
+
+impl ParamHotkeys {
+    /// Returns `None`, unless the hotkeys are enabled in the shader source code.
+    pub fn new(
+        identifier: &str,
+        description: &str,
+        actions: &[HotkeyAction],
+        preprocess_result: &PreprocessResult,
+    ) -> Result<Option<Self>, Cow<'static, str>> {
+        let enabled = preprocess_result.parse::<bool>(&format!("{}__hotkey", identifier))
+            .transpose()?
+            .unwrap_or(false);
+
+        if !enabled {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            identifier: identifier.to_string(),
+            description: description.to_string(),
+            actions: actions.to_vec(),
+            hotkeys: Vec::new(),
+            events: HotkeyEvents::default(),
+        }))
+    }
+
+    /// Registers the hotkeys on the filter.
+    /// The key combinations of hotkeys of the same name in `previous` are kept,
+    /// as the hotkeys are registered anew whenever the shader is reloaded.
+    pub fn register(&mut self, filter: *mut obs_source_t, previous: &[&mut ParamHotkeys]) {
+        for action in &self.actions {
+            let hotkey = Hotkey::register(
+                filter,
+                format!("shaderfilter_plus.{}.{}", self.identifier, action.name()),
+                &format!("{} - {}", self.description, action.description()),
+                *action,
+                self.events.clone(),
+            );
+            let previous_hotkey = previous.iter()
+                .flat_map(|previous| previous.hotkeys.iter())
+                .find(|previous_hotkey| previous_hotkey.name == hotkey.name);
+
+            if let Some(previous_hotkey) = previous_hotkey {
+                hotkey.copy_bindings(previous_hotkey);
+            }
+
+            self.hotkeys.push(hotkey);
+        }
+    }
+
+    /// Returns the events of the hotkeys since the last call.
+    pub fn take_events(&self) -> Vec<HotkeyEvent> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
+}
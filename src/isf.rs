@@ -0,0 +1,289 @@
+use std::borrow::Cow;
+use std::path::Path;
+use regex::{Regex, NoExpand};
+use serde_json::Value;
+
+/// The JSON header of an ISF shader, `/*{ ... }*/`, at the start of the file.
+fn header_pattern() -> Regex {
+    Regex::new(r"(?s)\A\s*/\*\s*(?P<json>\{.*?\})\s*\*/").unwrap()
+}
+
+/// Returns `true`, if the shader is written in the Interactive Shader Format,
+/// that is, it has the `.fs` extension and starts with a JSON header.
+pub fn is_isf(source_path: &Path, source: &str) -> bool {
+    source_path.extension().map(|extension| extension == "fs").unwrap_or(false)
+        && header_pattern().is_match(source)
+}
+
+fn format_number(value: &Value) -> Option<String> {
+    value.as_f64().map(|value| value.to_string())
+}
+
+/// Formats an RGBA color with components from 0 to 1 as `RRGGBBAA`.
+fn format_color(value: &Value) -> Option<String> {
+    let components = value.as_array()?;
+
+    if components.len() != 4 {
+        return None;
+    }
+
+    components.iter()
+        .map(|component| {
+            component.as_f64().map(|component| format!("{:02X}", (component.clamp(0.0, 1.0) * 255.0).round() as u8))
+        })
+        .collect()
+}
+
+/// Generates the declarations of an input of the JSON header, one per line.
+fn translate_input(input: &Value) -> Result<Vec<String>, Cow<'static, str>> {
+    let name = input.get("NAME").and_then(Value::as_str)
+        .ok_or_else(|| "An ISF input is missing its `NAME`.")?;
+    let input_type = input.get("TYPE").and_then(Value::as_str)
+        .ok_or_else(|| format!("ISF input `{}` is missing its `TYPE`.", name))?;
+    let mut lines = Vec::new();
+    let set = |lines: &mut Vec<String>, property: &str, value: Option<String>| {
+        if let Some(value) = value {
+            lines.push(format!("#pragma shaderfilter set {}__{} {}", name, property, value));
+        }
+    };
+
+    set(&mut lines, "description", input.get("LABEL").and_then(Value::as_str).map(str::to_string));
+
+    match input_type {
+        "float" => {
+            set(&mut lines, "default", input.get("DEFAULT").and_then(format_number));
+            set(&mut lines, "min", input.get("MIN").and_then(format_number));
+            set(&mut lines, "max", input.get("MAX").and_then(format_number));
+
+            if input.get("MIN").is_some() && input.get("MAX").is_some() {
+                set(&mut lines, "slider", Some("true".to_string()));
+            }
+
+            lines.push(format!("uniform float {};", name));
+        },
+        "bool" | "event" => {
+            set(&mut lines, "default", input.get("DEFAULT").and_then(|value| {
+                value.as_bool().or_else(|| value.as_f64().map(|value| value != 0.0))
+            }).map(|value| value.to_string()));
+            lines.push(format!("uniform bool {};", name));
+        },
+        "long" => {
+            let values = input.get("VALUES").and_then(Value::as_array)
+                .map(|values| values.iter().filter_map(Value::as_i64).collect::<Vec<_>>())
+                .unwrap_or_default();
+
+            let labels = input.get("LABELS").and_then(Value::as_array)
+                .map(|labels| labels.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+                .unwrap_or_default();
+
+            if !values.is_empty() && labels.len() == values.len() {
+                set(&mut lines, "options", Some(
+                    labels.iter().zip(&values)
+                        .map(|(label, value)| format!("{}={}", label, value))
+                        .collect::<Vec<_>>()
+                        .join(";")
+                ));
+            }
+
+            set(&mut lines, "default", input.get("DEFAULT").and_then(Value::as_i64).map(|value| value.to_string()));
+            set(&mut lines, "min", input.get("MIN").and_then(Value::as_i64).or_else(|| values.iter().copied().min()).map(|value| value.to_string()));
+            set(&mut lines, "max", input.get("MAX").and_then(Value::as_i64).or_else(|| values.iter().copied().max()).map(|value| value.to_string()));
+            lines.push(format!("uniform int {};", name));
+        },
+        "color" => {
+            set(&mut lines, "default", input.get("DEFAULT").and_then(format_color));
+            lines.push(format!("uniform float4 {};", name));
+        },
+        "point2D" => {
+            for (index, component) in ["x", "y"].iter().enumerate() {
+                let get = |key: &str| input.get(key).and_then(|value| value.get(index)).and_then(format_number);
+
+                set(&mut lines, &format!("{}__default", component), get("DEFAULT"));
+                set(&mut lines, &format!("{}__min", component), get("MIN"));
+                set(&mut lines, &format!("{}__max", component), get("MAX"));
+            }
+
+            lines.push(format!("uniform float2 {};", name));
+        },
+        // The source the filter is applied to, which is available as `inputImage` already
+        "image" if name == "inputImage" => (),
+        "image" => {
+            lines.push(format!("uniform texture2d {};", name));
+        },
+        "audio" => {
+            set(&mut lines, "length", input.get("MAX").and_then(Value::as_i64).map(|value| value.to_string()));
+            lines.push(format!("uniform texture2d builtin_texture_audio_{};", name));
+            lines.push(format!("#define {} builtin_texture_audio_{}", name, name));
+        },
+        "audioFFT" => {
+            set(&mut lines, "bins", input.get("MAX").and_then(Value::as_i64).map(|value| value.to_string()));
+            lines.push(format!("uniform texture2d builtin_texture_fft_{};", name));
+            lines.push(format!("#define {} builtin_texture_fft_{}", name, name));
+        },
+        _ => throw!(format!("ISF input `{}` has an unsupported type `{}`.", name, input_type)),
+    }
+
+    Ok(lines)
+}
+
+/// Translates an ISF shader to a shader using the ISF effect template.
+/// The JSON header is replaced with the declarations of the inputs and passes.
+/// Returns the translated shader and the number of lines the body was moved down by,
+/// which is non-zero only if the declarations are longer than the header.
+pub fn translate(source: &str) -> Result<(String, usize), Cow<'static, str>> {
+    let captures = header_pattern().captures(source)
+        .ok_or_else(|| "The ISF shader is missing its JSON header.")?;
+    let header = captures.get(0).unwrap();
+    let json: Value = serde_json::from_str(captures.name("json").unwrap().as_str())
+        .map_err(|err| format!("Could not parse the ISF JSON header: {}", err))?;
+    let inputs = json.get("INPUTS").and_then(Value::as_array).cloned().unwrap_or_default();
+    let passes = json.get("PASSES").and_then(Value::as_array).cloned().unwrap_or_default();
+    let mut lines = vec!["#pragma shaderfilter isf".to_string()];
+
+    for input in &inputs {
+        lines.extend(translate_input(input)?);
+    }
+
+    // All passes but the last one are rendered into intermediate textures, the last pass is rendered to the output
+    let mut targets = Vec::new();
+
+    for (index, pass) in passes.iter().enumerate() {
+        let target = pass.get("TARGET").and_then(Value::as_str);
+        let persistent = pass.get("PERSISTENT").and_then(|value| {
+            value.as_bool().or_else(|| value.as_f64().map(|value| value != 0.0))
+        }).unwrap_or(false);
+
+        if index == passes.len() - 1 {
+            // The output of the last pass is kept as the previous output of the filter
+            if let (Some(target), true) = (target, persistent) {
+                lines.push("uniform texture2d builtin_texture_previous_output;".to_string());
+                lines.push(format!("#define {} builtin_texture_previous_output", target));
+            }
+
+            continue;
+        }
+
+        // Passes without a target cannot be sampled, but are still rendered, so that `PASSINDEX` matches
+        let target = match target {
+            Some(target) => {
+                lines.push(format!("#define {} builtin_texture_pass_{}", target, target));
+                target.to_string()
+            },
+            None => format!("isf_pass_{}", index),
+        };
+
+        lines.push(format!("#pragma shaderfilter pass {}{}", target, if persistent { " persistent" } else { "" }));
+        targets.push(target);
+    }
+
+    lines.push(format!("#define PASSINDEX (builtin_pass_index < 0 ? {} : builtin_pass_index)", targets.len()));
+
+    // The body starts on the last line of the header
+    let header_line_count = header.as_str().matches('\n').count() + 1;
+
+    while lines.len() + 1 < header_line_count {
+        lines.push(String::new());
+    }
+
+    let line_offset = lines.len() + 1 - header_line_count;
+
+    let body = &source[header.end()..];
+    let pattern_main = Regex::new(r"\bvoid\s+main\s*\(\s*(?:void)?\s*\)").unwrap();
+
+    if !pattern_main.is_match(body) {
+        throw!("The ISF shader is missing its `void main()` function.");
+    }
+
+    let body = pattern_main.replace(body, NoExpand("void builtin_isf_main(inout float4 gl_FragColor, float2 isf_FragNormCoord)"));
+    let mut result = format!("{}\n{}", lines.join("\n"), body);
+
+    result.push_str("
+
+float4 builtin_isf_render(float2 uv) {
+    float4 gl_FragColor = float4(0.0, 0.0, 0.0, 0.0);
+    builtin_isf_main(gl_FragColor, builtin_isf_flip(uv));
+    return gl_FragColor;
+}
+");
+
+    for target in &targets {
+        result.push_str(&format!("
+float4 render_pass_{}(float2 uv) {{
+    return builtin_isf_render(uv);
+}}
+", target));
+    }
+
+    result.push_str("
+float4 render(float2 uv) {
+    return builtin_isf_render(uv);
+}
+");
+
+    Ok((result, line_offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The zero-based index of the first line containing `pattern`.
+    fn find_line(source: &str, pattern: &str) -> usize {
+        source.lines().position(|line| line.contains(pattern)).unwrap()
+    }
+
+    #[test]
+    fn body_keeps_its_lines_below_a_long_header() {
+        let source = "/*{\n\"INPUTS\": [\n{\"NAME\": \"amount\", \"TYPE\": \"float\"}\n],\n\"PASSES\": []\n}*/\nvoid main() {\n}\n";
+        let (translated, line_offset) = translate(source).unwrap();
+
+        assert_eq!(line_offset, 0);
+        assert_eq!(find_line(&translated, "void builtin_isf_main("), find_line(source, "void main()"));
+        assert!(translated.contains("uniform float amount;"));
+    }
+
+    #[test]
+    fn body_is_moved_down_below_a_short_header() {
+        let source = "/*{\"INPUTS\": [{\"NAME\": \"a\", \"TYPE\": \"float\"}, {\"NAME\": \"b\", \"TYPE\": \"bool\"}]}*/\nvoid main() {\n}\n";
+        let (translated, line_offset) = translate(source).unwrap();
+
+        assert_eq!(line_offset, 4);
+        assert_eq!(find_line(&translated, "void builtin_isf_main("), find_line(source, "void main()") + line_offset);
+    }
+
+    #[test]
+    fn inputs_and_passes_are_declared() {
+        let source = r#"/*{
+    "INPUTS": [
+        {"NAME": "mode", "TYPE": "long", "VALUES": [0, 2], "LABELS": ["Off", "On"], "DEFAULT": 2},
+        {"NAME": "inputImage", "TYPE": "image"}
+    ],
+    "PASSES": [
+        {"TARGET": "blurred", "PERSISTENT": true},
+        {}
+    ]
+}*/
+void main() {
+}
+"#;
+        let (translated, _) = translate(source).unwrap();
+
+        assert!(translated.contains("#pragma shaderfilter set mode__options Off=0;On=2\n"));
+        assert!(translated.contains("#pragma shaderfilter set mode__default 2\n"));
+        assert!(translated.contains("#pragma shaderfilter set mode__min 0\n"));
+        assert!(translated.contains("#pragma shaderfilter set mode__max 2\n"));
+        assert!(translated.contains("uniform int mode;"));
+        assert!(!translated.contains("inputImage;"));
+        assert!(translated.contains("#define blurred builtin_texture_pass_blurred\n"));
+        assert!(translated.contains("#pragma shaderfilter pass blurred persistent\n"));
+        assert!(translated.contains("#define PASSINDEX (builtin_pass_index < 0 ? 1 : builtin_pass_index)"));
+        assert!(translated.contains("float4 render_pass_blurred(float2 uv)"));
+    }
+
+    #[test]
+    fn invalid_shaders_are_rejected() {
+        assert!(translate("void main() {}").is_err());
+        assert!(translate("/*{\"INPUTS\": [{\"NAME\": \"a\", \"TYPE\": \"cube\"}]}*/\nvoid main() {}").is_err());
+        assert!(translate("/*{}*/\nvoid render() {}").is_err());
+    }
+}
@@ -4,15 +4,17 @@
 #![feature(associated_type_bounds)]
 
 use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{RwLock, Arc, Weak};
+use std::sync::{Mutex, RwLock, Arc, Weak};
 use std::borrow::Cow;
 use std::time::Instant;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs::File;
 use std::ffi::{CStr, CString};
 use std::io::Read;
 use ordered_float::OrderedFloat;
+use smallvec::{SmallVec, smallvec};
 use lazy_static::lazy_static;
 use obs_wrapper::{
     info::*,
@@ -26,8 +28,13 @@ use obs_wrapper::{
 use fourier::*;
 use num_complex::Complex;
 use util::*;
+use audio_input::*;
 use effect::*;
 use preprocessor::*;
+use watcher::*;
+use render::*;
+use properties::*;
+use hotkeys::*;
 
 macro_rules! throw {
     ($e:expr) => {{
@@ -37,8 +44,14 @@ macro_rules! throw {
 }
 
 mod util;
+mod audio_input;
 mod effect;
 mod preprocessor;
+mod isf;
+mod watcher;
+mod render;
+mod properties;
+mod hotkeys;
 
 lazy_static! {
     static ref GLOBAL_STATE: GlobalState = Default::default();
@@ -70,6 +83,36 @@ pub enum WindowFunction {
 }
 
 impl WindowFunction {
+    pub const NAMES: &'static [&'static str] = &[
+        "None", "Blackman", "Cosine", "Hamming", "Hanning", "Nuttall", "Triangular",
+    ];
+
+    /// Parses one of `NAMES`.
+    /// The coefficients are only used by the `Cosine` window function.
+    pub fn from_name(name: &str, cosine_coefficients: [f64; 4]) -> Option<Self> {
+        use WindowFunction::*;
+
+        Some(match name {
+            "None" => None,
+            "Blackman" => Blackman,
+            "Cosine" => {
+                let [a, b, c, d] = cosine_coefficients;
+
+                Cosine {
+                    a: OrderedFloat(a),
+                    b: OrderedFloat(b),
+                    c: OrderedFloat(c),
+                    d: OrderedFloat(d),
+                }
+            },
+            "Hamming" => Hamming,
+            "Hanning" => Hanning,
+            "Nuttall" => Nuttall,
+            "Triangular" => Triangular,
+            _ => return Option::None,
+        })
+    }
+
     pub fn generate_coefficients(self, len: usize) -> Vec<f32> {
         use apodize::*;
         use WindowFunction::*;
@@ -86,117 +129,1229 @@ impl WindowFunction {
     }
 }
 
+/// The distribution of frequencies along the bins of the frequency spectrum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FrequencyScale {
+    Linear,
+    Logarithmic,
+    Mel,
+    /// Bands of a third of an octave, centered around the standard frequencies
+    /// `1000 * 2^(n/3)` Hz. The number of bins is determined by the sample rate.
+    ThirdOctave,
+}
+
+impl FrequencyScale {
+    pub const NAMES: &'static [&'static str] = &[
+        "Linear", "Logarithmic", "Mel", "ThirdOctave",
+    ];
+
+    /// The lowest frequency of non-linear scales, in Hz.
+    const MIN_FREQUENCY: f64 = 20.0;
+
+    /// Parses one of `NAMES`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        use FrequencyScale::*;
+
+        Some(match name {
+            "Linear" => Linear,
+            "Logarithmic" => Logarithmic,
+            "Mel" => Mel,
+            "ThirdOctave" => ThirdOctave,
+            _ => return None,
+        })
+    }
+
+    fn hz_to_mel(frequency: f64) -> f64 {
+        2595.0 * (1.0 + frequency / 700.0).log10()
+    }
+
+    fn mel_to_hz(mel: f64) -> f64 {
+        700.0 * (10f64.powf(mel / 2595.0) - 1.0)
+    }
+
+    /// Computes the `bins + 1` edges of the output bins, in Hz.
+    fn bin_edges(self, bins: usize, nyquist_frequency: f64) -> Vec<f64> {
+        use FrequencyScale::*;
+
+        let interpolate = |min: f64, max: f64, map: &dyn Fn(f64) -> f64| {
+            (0..=bins)
+                .map(|index| map(min + (max - min) * index as f64 / bins as f64))
+                .collect::<Vec<_>>()
+        };
+
+        match self {
+            Linear => interpolate(0.0, nyquist_frequency, &|frequency| frequency),
+            Logarithmic => interpolate(
+                Self::MIN_FREQUENCY.ln(),
+                nyquist_frequency.ln(),
+                &f64::exp,
+            ),
+            Mel => interpolate(
+                Self::hz_to_mel(Self::MIN_FREQUENCY),
+                Self::hz_to_mel(nyquist_frequency),
+                &Self::mel_to_hz,
+            ),
+            ThirdOctave => {
+                let band_edge = |band: i32| 1000.0 * 2f64.powf((band as f64 - 0.5) / 3.0);
+                let mut band = (3.0 * (Self::MIN_FREQUENCY / 1000.0).log2()).ceil() as i32;
+                let mut edges = vec![band_edge(band)];
+
+                while band_edge(band + 1) <= nyquist_frequency {
+                    band += 1;
+                    edges.push(band_edge(band));
+                }
+
+                edges
+            },
+        }
+    }
+
+    /// Resamples a linear frequency spectrum to `bins` bins distributed along this scale.
+    /// Bins spanning multiple input bins are averaged, narrower bins are linearly interpolated.
+    /// If `bins` is `0`, the number of bins of the input spectrum is used.
+    pub fn resample(self, spectrum: &[f32], bins: usize, nyquist_frequency: f64) -> Vec<f32> {
+        let bins = if bins == 0 { spectrum.len() } else { bins };
+
+        if spectrum.is_empty() || (self == FrequencyScale::Linear && bins == spectrum.len()) {
+            return spectrum.to_vec();
+        }
+
+        let len = spectrum.len();
+        let max_position = (len - 1) as f64;
+        let frequency_to_position = |frequency: f64| frequency / nyquist_frequency * len as f64;
+
+        self.bin_edges(bins, nyquist_frequency).windows(2).map(|edges| {
+            let start = frequency_to_position(edges[0]).clamp(0.0, max_position);
+            let end = frequency_to_position(edges[1]).clamp(0.0, max_position);
+
+            if end - start < 1.0 {
+                let position = (start + end) / 2.0;
+                let index = position.floor() as usize;
+                let next_index = (index + 1).min(len - 1);
+                let t = (position - index as f64) as f32;
+
+                spectrum[index] * (1.0 - t) + spectrum[next_index] * t
+            } else {
+                let range = &spectrum[start.round() as usize..end.round() as usize];
+
+                range.iter().sum::<f32>() / range.len() as f32
+            }
+        }).collect::<Vec<_>>()
+    }
+}
+
+/// The arrangement of the spectra of multiple channels in a single texture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FFTChannelLayout {
+    /// Only the selected channel.
+    Single,
+    /// One row per channel.
+    Rows,
+    /// The channels side by side, left to right.
+    Columns,
+    /// Up to four channels in the RGBA components.
+    RGBA,
+}
+
+impl FFTChannelLayout {
+    pub const NAMES: &'static [&'static str] = &[
+        "Single", "Rows", "Columns", "RGBA",
+    ];
+
+    /// Parses one of `NAMES`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        use FFTChannelLayout::*;
+
+        Some(match name {
+            "Single" => Single,
+            "Rows" => Rows,
+            "Columns" => Columns,
+            "RGBA" => RGBA,
+            _ => return None,
+        })
+    }
+
+    /// The zero-based channels to analyze.
+    /// The packed layouts only include channels of the current speaker layout.
+    pub fn channels(self, selected_channel: usize) -> Vec<usize> {
+        let channel_count = get_audio_channel_count();
+
+        match self {
+            FFTChannelLayout::Single => vec![selected_channel],
+            FFTChannelLayout::RGBA => (0..channel_count.min(4)).collect(),
+            _ => (0..channel_count).collect(),
+        }
+    }
+
+    /// Arranges the spectra of equal length into texels.
+    /// Returns the texels, the dimensions of the texture and the number of components per texel.
+    pub fn pack(self, spectra: &[Vec<f32>]) -> (Vec<f32>, [usize; 2], usize) {
+        let bins = spectra.first().map(|spectrum| spectrum.len()).unwrap_or(0);
+
+        match self {
+            FFTChannelLayout::Single => (spectra[0].clone(), [bins, 1], 1),
+            FFTChannelLayout::Rows => (spectra.concat(), [bins, spectra.len()], 1),
+            FFTChannelLayout::Columns => (spectra.concat(), [bins * spectra.len(), 1], 1),
+            FFTChannelLayout::RGBA => {
+                let mut texels = vec![0.0; bins * 4];
+
+                for (component, spectrum) in spectra.iter().enumerate().take(4) {
+                    for (bin, value) in spectrum.iter().enumerate() {
+                        texels[bin * 4 + component] = *value;
+                    }
+                }
+
+                (texels, [bins, 1], 4)
+            },
+        }
+    }
+}
+
+/// The quantity stored in the bins of the frequency spectrum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FFTOutput {
+    /// The square root of the amplitude, kept for compatibility with existing shaders.
+    AmplitudeSqrt,
+    Amplitude,
+    Power,
+    /// The amplitude in decibels, clamped to the range from `floor` to `ceiling` and mapped to `0..1`.
+    Decibels {
+        floor: OrderedFloat<f64>,
+        ceiling: OrderedFloat<f64>,
+    },
+}
+
+impl FFTOutput {
+    pub const NAMES: &'static [&'static str] = &[
+        "AmplitudeSqrt", "Amplitude", "Power", "Decibels",
+    ];
+
+    /// Parses one of `NAMES`.
+    /// The range is only used by the `Decibels` output.
+    pub fn from_name(name: &str, decibels_floor: f64, decibels_ceiling: f64) -> Option<Self> {
+        use FFTOutput::*;
+
+        Some(match name {
+            "AmplitudeSqrt" => AmplitudeSqrt,
+            "Amplitude" => Amplitude,
+            "Power" => Power,
+            "Decibels" => Decibels {
+                floor: OrderedFloat(decibels_floor),
+                ceiling: OrderedFloat(decibels_ceiling),
+            },
+            _ => return None,
+        })
+    }
+
+    /// Converts the single-sided amplitude of a frequency bin.
+    pub fn convert(self, amplitude: f32) -> f32 {
+        use FFTOutput::*;
+
+        match self {
+            AmplitudeSqrt => (amplitude * 2.0).sqrt(),
+            Amplitude => amplitude,
+            Power => amplitude * amplitude,
+            Decibels { floor, ceiling } => {
+                let decibels = 20.0 * (amplitude as f64).max(std::f64::MIN_POSITIVE).log10();
+
+                ((decibels - *floor) / (*ceiling - *floor)).clamp(0.0, 1.0) as f32
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct GlobalStateAudioFFTDescriptor {
-    mix: usize,
+    input: AudioInputDescriptor,
     channel: usize,
     dampening_factor_attack: OrderedFloat<f64>,
     dampening_factor_release: OrderedFloat<f64>,
     window_function: WindowFunction,
+    /// The number of samples analyzed at once.
+    fft_size: usize,
+    /// The number of new samples required before the next analysis is performed.
+    hop_size: usize,
+    output: FFTOutput,
+    /// The time in seconds it takes the running peak used for normalization to decay by half,
+    /// or `None`, if the spectrum should not be normalized.
+    auto_gain_half_life: Option<OrderedFloat<f64>>,
 }
 
 impl GlobalStateAudioFFTDescriptor {
     pub fn new(
-        mix: usize,
+        input: AudioInputDescriptor,
         channel: usize,
         dampening_factor_attack: f64,
         dampening_factor_release: f64,
         window_function: WindowFunction,
+        fft_size: usize,
+        overlap: f64,
+        output: FFTOutput,
+        auto_gain_half_life: Option<f64>,
+    ) -> Self {
+        let hop_size = ((fft_size as f64 * (1.0 - overlap.clamp(0.0, 1.0))).round() as usize).max(1);
+
+        Self {
+            input,
+            channel,
+            dampening_factor_attack: OrderedFloat(dampening_factor_attack),
+            dampening_factor_release: OrderedFloat(dampening_factor_release),
+            window_function,
+            fft_size,
+            hop_size,
+            output,
+            auto_gain_half_life: auto_gain_half_life.map(OrderedFloat),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct FFTResult {
+    batch_number: usize,
+    frequency_spectrum: Arc<Vec<f32>>,
+}
+
+/// The FFT plan and buffers for a specific FFT size, reused between batches
+/// to avoid allocations on the audio thread.
+pub struct FFTPlan {
+    fft: Mutex<Box<dyn Fft<Real = f32> + Send>>,
+    window: Vec<f32>,
+    /// The windowed samples, transformed in place.
+    buffer: Vec<Complex<f32>>,
+    /// The spectrum of the current batch, before dampening.
+    spectrum: Vec<f32>,
+}
+
+impl FFTPlan {
+    fn new(len: usize, window_function: WindowFunction) -> Self {
+        Self {
+            fft: Mutex::new(fourier::create_fft_f32(len)),
+            window: window_function.generate_coefficients(len),
+            buffer: vec![Complex::new(0.0, 0.0); len],
+            spectrum: Vec::with_capacity(len / 2),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Returns the plan stored in `plan`, which is only created if missing or if the FFT size changed.
+    fn get_or_create(plan: &mut Option<FFTPlan>, len: usize, window_function: WindowFunction) -> &mut FFTPlan {
+        if plan.as_ref().map(|plan| plan.len() != len).unwrap_or(true) {
+            *plan = Some(FFTPlan::new(len, window_function));
+        }
+
+        plan.as_mut().unwrap()
+    }
+}
+
+pub struct GlobalStateAudioFFTMutable {
+    audio_input: Option<AudioInput>,
+    /// A ring buffer of the most recent `fft_size` samples.
+    sample_buffer: VecDeque<f32>,
+    /// The number of samples received since the last analysis.
+    samples_since_analysis: usize,
+    /// Created on the first analysis and whenever the FFT size changes.
+    plan: Option<FFTPlan>,
+    /// The dampened spectrum of the previous batch, before normalization.
+    dampened_spectrum: Vec<f32>,
+    /// The running peak of the dampened spectrum, used for normalization.
+    auto_gain_peak: f32,
+    /// Set during `retrieve_result` to indicate that the analysis of the next
+    /// batch should be performed.
+    next_batch_scheduled: AtomicBool,
+    /// The result of the analysis.
+    result: Option<FFTResult>,
+}
+
+impl Default for GlobalStateAudioFFTMutable {
+    fn default() -> Self {
+        Self {
+            audio_input: None,
+            sample_buffer: Default::default(),
+            samples_since_analysis: 0,
+            plan: None,
+            dampened_spectrum: Vec::new(),
+            auto_gain_peak: 0.0,
+            next_batch_scheduled: AtomicBool::new(true),
+            result: None,
+        }
+    }
+}
+
+pub struct GlobalStateAudioFFT {
+    descriptor: GlobalStateAudioFFTDescriptor,
+    mutable: Arc<RwLock<GlobalStateAudioFFTMutable>>,
+}
+
+impl GlobalStateAudioFFT {
+    fn get_nyquist_frequency() -> f64 {
+        let audio_info = ObsAudioInfo::get()
+            .expect("Audio info not accessible.");
+
+        audio_info.samples_per_second() as f64 / 2.0
+    }
+
+    fn samples_to_time_elapsed(samples: usize) -> f64 {
+        let audio_info = ObsAudioInfo::get()
+            .expect("Audio info not accessible.");
+
+        samples as f64 / audio_info.samples_per_second() as f64
+    }
+
+    /// Stores the spectrum of the samples in `plan.spectrum`.
+    fn perform_analysis(
+        samples: impl Iterator<Item=f32> + ExactSizeIterator,
+        plan: &mut FFTPlan,
+        output: FFTOutput,
+    ) {
+        assert_eq!(samples.len(), plan.len());
+
+        let len = plan.len();
+
+        plan.buffer.iter_mut()
+            .zip(samples.zip(plan.window.iter()))
+            .for_each(|(complex, (sample, window_coefficient))| {
+                *complex = Complex::new(sample * window_coefficient, 0.0);
+            });
+
+        plan.fft.lock().unwrap().transform_in_place(&mut plan.buffer, Transform::Fft);
+
+        plan.spectrum.clear();
+        plan.spectrum.extend(plan.buffer.iter().take(len / 2).map(|complex| {
+            // normalize according to https://www.sjsu.edu/people/burford.furman/docs/me120/FFT_tutorial_NI.pdf
+            output.convert(complex.norm() * 2.0 / len as f32)
+        }));
+    }
+
+    fn process_audio_data(this: &Weak<Self>, audio_data: &AudioSamples) {
+        let this = if let Some(this) = Weak::upgrade(this) {
+            this
+        } else {
+            // The audio FFT component no longer exists, bail.
+            return;
+        };
+
+        let current_samples = if let Some(samples) = audio_data.samples_normalized(this.descriptor.channel) {
+            samples
+        } else {
+            // No samples captured, bail.
+            return;
+        };
+
+        this.process_samples(current_samples, Self::get_nyquist_frequency() * 2.0);
+    }
+
+    /// Appends the samples to the analysis window and analyzes it, if a new hop is complete
+    /// and the results are being requested.
+    fn process_samples(&self, current_samples: &[f32], sample_rate: f64) {
+        let mut mutable_write = self.mutable.write().unwrap();
+        let mutable = &mut *mutable_write;
+        let fft_size = self.descriptor.fft_size;
+
+        // Start with silence, so that the analysis window is always filled
+        if mutable.sample_buffer.len() != fft_size {
+            mutable.sample_buffer = std::iter::repeat(0.0).take(fft_size).collect();
+        }
+
+        mutable.sample_buffer.extend(current_samples);
+
+        // Only keep the most recent samples
+        let samples_received = mutable.sample_buffer.len() - fft_size;
+
+        mutable.sample_buffer.drain(0..samples_received);
+        mutable.samples_since_analysis += samples_received;
+
+        // Analyze the window at most once per hop, and only if the results are being requested.
+        if mutable.samples_since_analysis < self.descriptor.hop_size
+            || !mutable.next_batch_scheduled.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let plan = FFTPlan::get_or_create(&mut mutable.plan, fft_size, self.descriptor.window_function);
+
+        Self::perform_analysis(mutable.sample_buffer.iter().copied(), plan, self.descriptor.output);
+
+        let time_elapsed = mutable.samples_since_analysis as f64 / sample_rate;
+        let analysis_result = &plan.spectrum;
+        let dampened_spectrum = &mut mutable.dampened_spectrum;
+
+        mutable.samples_since_analysis = 0;
+
+        // Dampen the result by mixing it with the result from the previous batch
+        if dampened_spectrum.len() == analysis_result.len()
+            && (*self.descriptor.dampening_factor_attack > 0.0 || *self.descriptor.dampening_factor_release > 0.0) {
+            let dampening_multiplier_attack = self.descriptor.dampening_factor_attack.powf(
+                time_elapsed
+            ).clamp(0.0, 1.0) as f32;
+            let dampening_multiplier_release = self.descriptor.dampening_factor_release.powf(
+                time_elapsed
+            ).clamp(0.0, 1.0) as f32;
+
+            dampened_spectrum.iter_mut()
+                .zip(analysis_result.iter())
+                .for_each(move |(previous, current)| {
+                    let dampening_multiplier = if *current > *previous {
+                        dampening_multiplier_attack
+                    } else {
+                        dampening_multiplier_release
+                    };
+
+                    *previous = dampening_multiplier * *previous + (1.0 - dampening_multiplier) * *current;
+                })
+        } else {
+            dampened_spectrum.clear();
+            dampened_spectrum.extend_from_slice(analysis_result);
+        }
+
+        // Reuse the previous result, unless it is still being used
+        let next_batch_number = mutable.result.as_ref()
+            .map(|result| result.batch_number + 1).unwrap_or(0);
+        let mut frequency_spectrum = mutable.result.take()
+            .map(|result| result.frequency_spectrum)
+            .unwrap_or_else(|| Arc::new(Vec::new()));
+
+        if Arc::get_mut(&mut frequency_spectrum).is_none() {
+            frequency_spectrum = Arc::new(Vec::with_capacity(dampened_spectrum.len()));
+        }
+
+        {
+            let result_spectrum = Arc::get_mut(&mut frequency_spectrum).unwrap();
+
+            result_spectrum.clear();
+            result_spectrum.extend_from_slice(dampened_spectrum);
+
+            // Normalize the result by the running peak, which decays over time
+            if let Some(auto_gain_half_life) = self.descriptor.auto_gain_half_life {
+                let current_peak = result_spectrum.iter().copied().fold(0.0, f32::max);
+                let decay_multiplier = 0.5f64.powf(time_elapsed / auto_gain_half_life.max(std::f64::EPSILON)) as f32;
+                let peak = current_peak.max(mutable.auto_gain_peak * decay_multiplier);
+
+                mutable.auto_gain_peak = peak;
+
+                if peak > std::f32::EPSILON {
+                    result_spectrum.iter_mut().for_each(|value| *value /= peak);
+                }
+            }
+        }
+
+        mutable.result = Some(FFTResult {
+            batch_number: next_batch_number,
+            frequency_spectrum,
+        });
+        mutable.next_batch_scheduled.swap(false, Ordering::SeqCst);
+    }
+}
+
+impl GlobalStateComponentType for GlobalStateAudioFFT {
+    type Descriptor = GlobalStateAudioFFTDescriptor;
+    type Result = FFTResult;
+
+    fn create(descriptor: &Self::Descriptor) -> Arc<Self> {
+        let result = Arc::new(Self {
+            descriptor: descriptor.clone(),
+            mutable: Default::default(),
+        });
+
+        let audio_input = AudioInput::new(
+            descriptor.input.clone(),
+            {
+                let self_cloned = Arc::downgrade(&result);
+
+                Arc::new(move |audio_data: &AudioSamples| {
+                    Self::process_audio_data(&self_cloned, audio_data);
+                })
+            },
+        );
+
+        result.mutable.write().unwrap().audio_input = Some(audio_input);
+
+        result
+    }
+
+    fn retrieve_result(self: &Arc<Self>) -> Option<Self::Result> {
+        let mutable_read = self.mutable.read().unwrap();
+
+        if let Some(audio_input) = mutable_read.audio_input.as_ref() {
+            audio_input.connect();
+        }
+
+        mutable_read.next_batch_scheduled.store(true, Ordering::SeqCst);
+        mutable_read.result.clone()
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct GlobalStateAudioWaveformDescriptor {
+    input: AudioInputDescriptor,
+    /// The channel to capture, ignored if `stereo` is set.
+    channel: usize,
+    /// The number of most recent samples to keep, per channel.
+    length: usize,
+    /// Whether to capture the first two channels, interleaved.
+    stereo: bool,
+}
+
+impl GlobalStateAudioWaveformDescriptor {
+    pub fn new(
+        input: AudioInputDescriptor,
+        channel: usize,
+        length: usize,
+        stereo: bool,
+    ) -> Self {
+        Self {
+            input,
+            channel,
+            length,
+            stereo,
+        }
+    }
+
+    fn channels(&self) -> SmallVec<[usize; 2]> {
+        if self.stereo {
+            smallvec![0, 1]
+        } else {
+            smallvec![self.channel]
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WaveformResult {
+    /// The number of channels the samples are interleaved from.
+    channels: usize,
+    /// The most recent samples, interleaved, oldest first.
+    samples: Arc<Vec<f32>>,
+}
+
+#[derive(Default)]
+pub struct GlobalStateAudioWaveformMutable {
+    audio_input: Option<AudioInput>,
+    /// A buffer of the most recent samples for each captured channel.
+    sample_buffers: SmallVec<[VecDeque<f32>; 2]>,
+}
+
+pub struct GlobalStateAudioWaveform {
+    descriptor: GlobalStateAudioWaveformDescriptor,
+    mutable: Arc<RwLock<GlobalStateAudioWaveformMutable>>,
+}
+
+impl GlobalStateAudioWaveform {
+    fn process_audio_data(this: &Weak<Self>, audio_data: &AudioSamples) {
+        let this = if let Some(this) = Weak::upgrade(this) {
+            this
+        } else {
+            // The audio waveform component no longer exists, bail.
+            return;
+        };
+
+        let mut mutable_write = this.mutable.write().unwrap();
+        let channels = this.descriptor.channels();
+
+        if mutable_write.sample_buffers.len() != channels.len() {
+            mutable_write.sample_buffers = channels.iter()
+                .map(|_| VecDeque::with_capacity(this.descriptor.length))
+                .collect();
+        }
+
+        for (channel, sample_buffer) in channels.iter().zip(mutable_write.sample_buffers.iter_mut()) {
+            if let Some(samples) = audio_data.samples_normalized(*channel) {
+                sample_buffer.extend(samples);
+            }
+
+            // Only keep the most recent samples
+            let samples_to_remove = sample_buffer.len().saturating_sub(this.descriptor.length);
+
+            sample_buffer.drain(0..samples_to_remove);
+        }
+    }
+}
+
+impl GlobalStateComponentType for GlobalStateAudioWaveform {
+    type Descriptor = GlobalStateAudioWaveformDescriptor;
+    type Result = WaveformResult;
+
+    fn create(descriptor: &Self::Descriptor) -> Arc<Self> {
+        let result = Arc::new(Self {
+            descriptor: descriptor.clone(),
+            mutable: Default::default(),
+        });
+
+        let audio_input = AudioInput::new(
+            descriptor.input.clone(),
+            {
+                let self_cloned = Arc::downgrade(&result);
+
+                Arc::new(move |audio_data: &AudioSamples| {
+                    Self::process_audio_data(&self_cloned, audio_data);
+                })
+            },
+        );
+
+        result.mutable.write().unwrap().audio_input = Some(audio_input);
+
+        result
+    }
+
+    fn retrieve_result(self: &Arc<Self>) -> Option<Self::Result> {
+        let mutable_read = self.mutable.read().unwrap();
+
+        if let Some(audio_input) = mutable_read.audio_input.as_ref() {
+            audio_input.connect();
+        }
+
+        let channels = mutable_read.sample_buffers.len();
+
+        if channels == 0 {
+            return None;
+        }
+
+        let length = self.descriptor.length;
+        let mut samples = vec![0.0; length * channels];
+
+        // Align the samples to the end of the texture, padding the beginning with silence
+        for (channel, sample_buffer) in mutable_read.sample_buffers.iter().enumerate() {
+            let offset = length - sample_buffer.len();
+
+            for (index, sample) in sample_buffer.iter().enumerate() {
+                samples[(offset + index) * channels + channel] = *sample;
+            }
+        }
+
+        Some(WaveformResult {
+            channels,
+            samples: Arc::new(samples),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct GlobalStateAudioLevelDescriptor {
+    input: AudioInputDescriptor,
+    channel: usize,
+    dampening_factor_attack: OrderedFloat<f64>,
+    dampening_factor_release: OrderedFloat<f64>,
+}
+
+impl GlobalStateAudioLevelDescriptor {
+    pub fn new(
+        input: AudioInputDescriptor,
+        channel: usize,
+        dampening_factor_attack: f64,
+        dampening_factor_release: f64,
+    ) -> Self {
+        Self {
+            input,
+            channel,
+            dampening_factor_attack: OrderedFloat(dampening_factor_attack),
+            dampening_factor_release: OrderedFloat(dampening_factor_release),
+        }
+    }
+
+    /// Blends the previous level with the current level, depending on the time elapsed.
+    fn dampen(&self, previous: f32, current: f32, time_elapsed: f64) -> f32 {
+        let dampening_factor = if current > previous {
+            self.dampening_factor_attack
+        } else {
+            self.dampening_factor_release
+        };
+        let dampening_multiplier = dampening_factor.powf(time_elapsed).clamp(0.0, 1.0) as f32;
+
+        dampening_multiplier * previous + (1.0 - dampening_multiplier) * current
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct AudioLevelResult {
+    /// The root mean square of the most recent samples, in linear amplitude.
+    rms: f32,
+    /// The largest absolute value of the most recent samples, in linear amplitude.
+    peak: f32,
+}
+
+impl AudioLevelResult {
+    /// The level in decibels corresponding to silence.
+    const DECIBELS_MIN: f32 = -100.0;
+
+    fn amplitude_to_decibels(amplitude: f32) -> f32 {
+        (20.0 * amplitude.log10()).max(Self::DECIBELS_MIN)
+    }
+
+    fn rms_decibels(&self) -> f32 {
+        Self::amplitude_to_decibels(self.rms)
+    }
+
+    fn peak_decibels(&self) -> f32 {
+        Self::amplitude_to_decibels(self.peak)
+    }
+}
+
+#[derive(Default)]
+pub struct GlobalStateAudioLevelMutable {
+    audio_input: Option<AudioInput>,
+    result: AudioLevelResult,
+}
+
+/// Measures the loudness of an audio channel.
+pub struct GlobalStateAudioLevel {
+    descriptor: GlobalStateAudioLevelDescriptor,
+    mutable: Arc<RwLock<GlobalStateAudioLevelMutable>>,
+}
+
+impl GlobalStateAudioLevel {
+    fn process_audio_data(this: &Weak<Self>, audio_data: &AudioSamples) {
+        let this = if let Some(this) = Weak::upgrade(this) {
+            this
+        } else {
+            // The audio level component no longer exists, bail.
+            return;
+        };
+
+        let current_samples = if let Some(samples) = audio_data.samples_normalized(this.descriptor.channel) {
+            samples
+        } else {
+            // No samples captured, bail.
+            return;
+        };
+
+        let (sample_count, sum_of_squares, peak) = current_samples.into_iter()
+            .fold((0usize, 0.0f32, 0.0f32), |(count, sum_of_squares, peak), sample| {
+                (count + 1, sum_of_squares + sample * sample, peak.max(sample.abs()))
+            });
+
+        if sample_count == 0 {
+            return;
+        }
+
+        let rms = (sum_of_squares / sample_count as f32).sqrt();
+        let time_elapsed = GlobalStateAudioFFT::samples_to_time_elapsed(sample_count);
+        let mut mutable_write = this.mutable.write().unwrap();
+        let previous = mutable_write.result.clone();
+
+        mutable_write.result = AudioLevelResult {
+            rms: this.descriptor.dampen(previous.rms, rms, time_elapsed),
+            peak: this.descriptor.dampen(previous.peak, peak, time_elapsed),
+        };
+    }
+}
+
+impl GlobalStateComponentType for GlobalStateAudioLevel {
+    type Descriptor = GlobalStateAudioLevelDescriptor;
+    type Result = AudioLevelResult;
+
+    fn create(descriptor: &Self::Descriptor) -> Arc<Self> {
+        let result = Arc::new(Self {
+            descriptor: descriptor.clone(),
+            mutable: Default::default(),
+        });
+
+        let audio_input = AudioInput::new(
+            descriptor.input.clone(),
+            {
+                let self_cloned = Arc::downgrade(&result);
+
+                Arc::new(move |audio_data: &AudioSamples| {
+                    Self::process_audio_data(&self_cloned, audio_data);
+                })
+            },
+        );
+
+        result.mutable.write().unwrap().audio_input = Some(audio_input);
+
+        result
+    }
+
+    fn retrieve_result(self: &Arc<Self>) -> Option<Self::Result> {
+        let mutable_read = self.mutable.read().unwrap();
+
+        if let Some(audio_input) = mutable_read.audio_input.as_ref() {
+            audio_input.connect();
+        }
+
+        Some(mutable_read.result.clone())
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct GlobalStateBeatDetectorDescriptor {
+    input: AudioInputDescriptor,
+    channel: usize,
+    /// The lowest frequency of the analyzed band, in Hz.
+    frequency_min: OrderedFloat<f64>,
+    /// The highest frequency of the analyzed band, in Hz.
+    frequency_max: OrderedFloat<f64>,
+    /// The multiple of the average spectral flux, above which an onset is considered a beat.
+    threshold: OrderedFloat<f64>,
+    /// The minimum time in seconds between two beats.
+    min_interval: OrderedFloat<f64>,
+}
+
+impl GlobalStateBeatDetectorDescriptor {
+
+    pub fn new(
+        input: AudioInputDescriptor,
+        channel: usize,
+        frequency_min: f64,
+        frequency_max: f64,
+        threshold: f64,
+        min_interval: f64,
     ) -> Self {
         Self {
-            mix,
+            input,
             channel,
-            dampening_factor_attack: OrderedFloat(dampening_factor_attack),
-            dampening_factor_release: OrderedFloat(dampening_factor_release),
-            window_function,
+            frequency_min: OrderedFloat(frequency_min),
+            frequency_max: OrderedFloat(frequency_max),
+            threshold: OrderedFloat(threshold),
+            min_interval: OrderedFloat(min_interval),
         }
     }
 }
 
 #[derive(Clone)]
-pub struct FFTResult {
-    batch_number: usize,
-    frequency_spectrum: Arc<Vec<f32>>,
+pub struct BeatResult {
+    /// The number of beats detected since the detector was created.
+    beat_count: usize,
+    /// The spectral flux of the most recent batch, relative to the beat threshold.
+    onset_strength: f32,
+    /// The time in seconds since the last beat, or since the detector was created.
+    time_since_beat: f32,
+    /// The estimated tempo in beats per minute, or `0`, if not enough beats were detected.
+    bpm: f32,
 }
 
-pub struct GlobalStateAudioFFTMutable {
-    audio_output: Option<AudioOutput>,
+pub struct GlobalStateBeatDetectorMutable {
+    audio_input: Option<AudioInput>,
+    /// The most recent `FFT_SIZE` samples.
     sample_buffer: VecDeque<f32>,
-    window: Arc<Vec<f32>>,
-    /// Set during `retrieve_result` to indicate that the analysis of the next
-    /// batch should be performed.
-    next_batch_scheduled: AtomicBool,
-    /// The result of the analysis.
-    result: Option<FFTResult>,
+    /// The number of samples received since the last analysis.
+    samples_since_analysis: usize,
+    /// The number of samples received since the detector was created, used to timestamp the batches.
+    samples_received: usize,
+    /// When the most recent samples were received, to advance `time_since_beat` between audio callbacks.
+    received_at: Instant,
+    plan: Option<FFTPlan>,
+    /// The log-compressed band of the last processed spectrum.
+    previous_band: Vec<f32>,
+    /// The spectral flux of the most recent batches.
+    flux_history: VecDeque<f32>,
+    onset_strength: f32,
+    beat_count: usize,
+    /// The time of the last beat in seconds of received audio.
+    last_beat: f64,
+    /// The intervals between the most recent beats, in seconds.
+    beat_intervals: VecDeque<f64>,
+    bpm: f32,
 }
 
-impl Default for GlobalStateAudioFFTMutable {
+impl Default for GlobalStateBeatDetectorMutable {
     fn default() -> Self {
         Self {
-            audio_output: Default::default(),
-            sample_buffer: Default::default(),
-            window: Arc::new(Vec::new()),
-            next_batch_scheduled: AtomicBool::new(true),
-            result: None,
+            audio_input: None,
+            // Start with silence, so that the analysis window is always filled
+            sample_buffer: std::iter::repeat(0.0).take(GlobalStateBeatDetector::FFT_SIZE).collect(),
+            samples_since_analysis: 0,
+            samples_received: 0,
+            received_at: Instant::now(),
+            plan: None,
+            previous_band: Vec::new(),
+            flux_history: VecDeque::new(),
+            onset_strength: 0.0,
+            beat_count: 0,
+            last_beat: 0.0,
+            beat_intervals: VecDeque::new(),
+            bpm: 0.0,
         }
     }
 }
 
-pub struct GlobalStateAudioFFT {
-    descriptor: GlobalStateAudioFFTDescriptor,
-    mutable: Arc<RwLock<GlobalStateAudioFFTMutable>>,
+/// Detects beats using spectral flux onset detection on a frequency band of the audio signal.
+/// Every batch is analyzed on the audio thread, so that no onsets are missed between video frames.
+pub struct GlobalStateBeatDetector {
+    descriptor: GlobalStateBeatDetectorDescriptor,
+    mutable: Arc<RwLock<GlobalStateBeatDetectorMutable>>,
 }
 
-impl GlobalStateAudioFFT {
-    fn get_samples_per_frame() -> usize {
-        let audio_info = ObsAudioInfo::get()
-            .expect("Audio info not accessible.");
-        let video_info = ObsVideoInfo::get()
-            .expect("Video info not accessible.");
-        let framerate = video_info.framerate();
+impl GlobalStateBeatDetector {
+    const FFT_SIZE: usize = 1024;
+    /// The number of samples between consecutive analyses, for an overlap of 50%.
+    const HOP_SIZE: usize = Self::FFT_SIZE / 2;
+    /// The number of batches the average spectral flux is computed from.
+    const FLUX_HISTORY_LEN: usize = 64;
+    /// The number of beat intervals the tempo is estimated from.
+    const BEAT_INTERVALS_LEN: usize = 16;
+    /// Beat intervals longer than this (in seconds) are not used to estimate the tempo.
+    const BEAT_INTERVAL_MAX: f64 = 2.0;
+    const BPM_MIN: f64 = 60.0;
+    const BPM_MAX: f64 = 180.0;
+
+    fn process_audio_data(this: &Weak<Self>, audio_data: &AudioSamples) {
+        let this = if let Some(this) = Weak::upgrade(this) {
+            this
+        } else {
+            // The beat detector component no longer exists, bail.
+            return;
+        };
+
+        let mut remaining_samples = if let Some(samples) = audio_data.samples_normalized(this.descriptor.channel) {
+            samples
+        } else {
+            // No samples captured, bail.
+            return;
+        };
+
+        let mut mutable_write = this.mutable.write().unwrap();
+        let mutable = &mut *mutable_write;
+
+        mutable.received_at = Instant::now();
+
+        // Analyze the window after each hop, even if multiple hops were received at once
+        while !remaining_samples.is_empty() {
+            let sample_count = (Self::HOP_SIZE - mutable.samples_since_analysis).min(remaining_samples.len());
+
+            mutable.sample_buffer.extend(&remaining_samples[..sample_count]);
+            mutable.sample_buffer.drain(0..sample_count);
+            mutable.samples_since_analysis += sample_count;
+            mutable.samples_received += sample_count;
+            remaining_samples = &remaining_samples[sample_count..];
 
-        (audio_info.samples_per_second() as usize * framerate.denominator as usize)
-            / framerate.numerator as usize
+            if mutable.samples_since_analysis < Self::HOP_SIZE {
+                break;
+            }
+
+            mutable.samples_since_analysis = 0;
+
+            let mut plan = mutable.plan.take();
+            let plan_ref = FFTPlan::get_or_create(&mut plan, Self::FFT_SIZE, WindowFunction::Hanning);
+
+            GlobalStateAudioFFT::perform_analysis(mutable.sample_buffer.iter().copied(), plan_ref, FFTOutput::Amplitude);
+            this.process_spectrum(mutable, &plan_ref.spectrum);
+            mutable.plan = plan;
+        }
     }
 
-    fn render_frames_to_time_elapsed(render_frames: usize) -> f64 {
-        let video_info = ObsVideoInfo::get()
-            .expect("Video info not accessible.");
-        let framerate = video_info.framerate();
+    fn process_spectrum(&self, mutable: &mut GlobalStateBeatDetectorMutable, spectrum: &[f32]) {
+        if spectrum.is_empty() {
+            return;
+        }
+
+        let len = spectrum.len();
+        let nyquist_frequency = GlobalStateAudioFFT::get_nyquist_frequency();
+        let frequency_to_bin = |frequency: f64| {
+            ((frequency / nyquist_frequency * len as f64).round().max(0.0) as usize).min(len - 1)
+        };
+        let start = frequency_to_bin(*self.descriptor.frequency_min);
+        let end = frequency_to_bin(*self.descriptor.frequency_max).max(start) + 1;
+        let band = spectrum[start..end].iter()
+            // Compress the dynamic range, so that onsets in quiet passages are detected as well
+            .map(|amplitude| (amplitude * 1000.0).ln_1p());
+
+        if mutable.previous_band.len() != end - start {
+            mutable.previous_band = band.collect();
+            return;
+        }
+
+        // The average increase in magnitude across the band
+        let flux = band.zip(mutable.previous_band.iter_mut())
+            .map(|(current, previous)| {
+                let increase = (current - *previous).max(0.0);
+
+                *previous = current;
+
+                increase
+            })
+            .sum::<f32>() / (end - start) as f32;
+        let flux_average = if mutable.flux_history.is_empty() {
+            flux
+        } else {
+            mutable.flux_history.iter().sum::<f32>() / mutable.flux_history.len() as f32
+        };
+        let threshold = (flux_average * *self.descriptor.threshold as f32).max(std::f32::EPSILON);
+
+        mutable.onset_strength = flux / threshold;
+        mutable.flux_history.push_back(flux);
+
+        if mutable.flux_history.len() > Self::FLUX_HISTORY_LEN {
+            mutable.flux_history.pop_front();
+        }
+
+        let now = GlobalStateAudioFFT::samples_to_time_elapsed(mutable.samples_received);
+        let interval = now - mutable.last_beat;
+
+        if flux > threshold && interval >= *self.descriptor.min_interval {
+            if mutable.beat_count > 0 && interval <= Self::BEAT_INTERVAL_MAX {
+                mutable.beat_intervals.push_back(interval);
+
+                if mutable.beat_intervals.len() > Self::BEAT_INTERVALS_LEN {
+                    mutable.beat_intervals.pop_front();
+                }
+
+                mutable.bpm = Self::estimate_bpm(&mutable.beat_intervals);
+            }
 
-        (render_frames as f64 * framerate.denominator as f64) / framerate.numerator as f64
+            mutable.beat_count += 1;
+            mutable.last_beat = now;
+        }
     }
 
-    fn perform_analysis(
-        samples: impl Iterator<Item=f32> + ExactSizeIterator,
-        window: &[f32],
-    ) -> Vec<f32> {
-        assert_eq!(samples.len(), window.len());
+    /// Computes the median tempo of the beat intervals, folded into the range
+    /// from `BPM_MIN` to `BPM_MAX` to make up for skipped or doubled beats.
+    fn estimate_bpm(beat_intervals: &VecDeque<f64>) -> f32 {
+        if beat_intervals.len() < 4 {
+            return 0.0;
+        }
+
+        let mut bpms = beat_intervals.iter().map(|interval| {
+            let mut bpm = 60.0 / interval;
+
+            while bpm < Self::BPM_MIN {
+                bpm *= 2.0;
+            }
 
-        let len = samples.len();
-        let mut fft_data: Vec<Complex<f32>> = samples.zip(window.iter()).map(|(sample, window_coefficient)| {
-            Complex::new(sample * window_coefficient, 0.0)
+            while bpm >= Self::BPM_MAX {
+                bpm /= 2.0;
+            }
+
+            bpm
         }).collect::<Vec<_>>();
-        let fft = fourier::create_fft_f32(len);
 
-        fft.transform_in_place(&mut fft_data, Transform::Fft);
+        bpms.sort_by(|a, b| a.partial_cmp(b).unwrap());
 
-        fft_data.into_iter().take(len / 2).map(|complex| {
-            // normalize according to https://www.sjsu.edu/people/burford.furman/docs/me120/FFT_tutorial_NI.pdf
-            (complex.norm() * 4.0 / len as f32).sqrt()
-        }).collect::<Vec<_>>()
+        bpms[bpms.len() / 2] as f32
+    }
+}
+
+impl GlobalStateComponentType for GlobalStateBeatDetector {
+    type Descriptor = GlobalStateBeatDetectorDescriptor;
+    type Result = BeatResult;
+
+    fn create(descriptor: &Self::Descriptor) -> Arc<Self> {
+        let result = Arc::new(Self {
+            descriptor: descriptor.clone(),
+            mutable: Default::default(),
+        });
+
+        let audio_input = AudioInput::new(
+            descriptor.input.clone(),
+            {
+                let self_cloned = Arc::downgrade(&result);
+
+                Arc::new(move |audio_data: &AudioSamples| {
+                    Self::process_audio_data(&self_cloned, audio_data);
+                })
+            },
+        );
+
+        result.mutable.write().unwrap().audio_input = Some(audio_input);
+
+        result
+    }
+
+    fn retrieve_result(self: &Arc<Self>) -> Option<Self::Result> {
+        let mutable_read = self.mutable.read().unwrap();
+
+        if let Some(audio_input) = mutable_read.audio_input.as_ref() {
+            audio_input.connect();
+        }
+
+        let time_received = GlobalStateAudioFFT::samples_to_time_elapsed(mutable_read.samples_received);
+        let time_since_beat = time_received - mutable_read.last_beat
+            + mutable_read.received_at.elapsed().as_secs_f64();
+
+        Some(BeatResult {
+            beat_count: mutable_read.beat_count,
+            onset_strength: mutable_read.onset_strength,
+            time_since_beat: time_since_beat as f32,
+            bpm: mutable_read.bpm,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct GlobalStatePitchDetectorDescriptor {
+    input: AudioInputDescriptor,
+    channel: usize,
+    frequency_min: OrderedFloat<f64>,
+    frequency_max: OrderedFloat<f64>,
+    threshold: OrderedFloat<f64>,
+}
+
+impl GlobalStatePitchDetectorDescriptor {
+    pub fn new(
+        input: AudioInputDescriptor,
+        channel: usize,
+        frequency_min: f64,
+        frequency_max: f64,
+        threshold: f64,
+    ) -> Self {
+        Self {
+            input,
+            channel,
+            frequency_min: OrderedFloat(frequency_min),
+            frequency_max: OrderedFloat(frequency_max),
+            threshold: OrderedFloat(threshold),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct PitchResult {
+    /// The most recent confidently estimated fundamental frequency, in Hz.
+    frequency: f32,
+    /// How periodic the most recent samples are, from 0 to 1.
+    confidence: f32,
+    /// The MIDI note number nearest to `frequency`, 69 being A4 (440 Hz).
+    note: f32,
+}
+
+impl PitchResult {
+    fn frequency_to_note(frequency: f32) -> f32 {
+        (69.0 + 12.0 * (frequency / 440.0).log2()).round()
+    }
+}
+
+#[derive(Default)]
+pub struct GlobalStatePitchDetectorMutable {
+    audio_input: Option<AudioInput>,
+    /// The most recent samples, enough to cover two periods of the lowest frequency.
+    sample_buffer: VecDeque<f32>,
+    /// Created on the first analysis and whenever the period range changes.
+    /// Its buffer holds the spectrum of the analysis window.
+    plan: Option<FFTPlan>,
+    /// The spectrum of all buffered samples.
+    spectrum: Vec<Complex<f32>>,
+    /// The difference function of the YIN algorithm, kept to avoid reallocations.
+    difference: Vec<f32>,
+    result: PitchResult,
+}
+
+/// Estimates the fundamental frequency of an audio channel using the YIN algorithm.
+pub struct GlobalStatePitchDetector {
+    descriptor: GlobalStatePitchDetectorDescriptor,
+    mutable: Arc<RwLock<GlobalStatePitchDetectorMutable>>,
+}
+
+impl GlobalStatePitchDetector {
+    /// The RMS below which the samples are considered silent.
+    const SILENCE_RMS: f32 = 1.0e-3;
+
+    /// The range of periods to search, in samples.
+    fn get_period_range(&self) -> (usize, usize) {
+        let sample_rate = GlobalStateAudioFFT::get_nyquist_frequency() * 2.0;
+        let period_min = (sample_rate / *self.descriptor.frequency_max).floor().max(2.0) as usize;
+        let period_max = (sample_rate / *self.descriptor.frequency_min).ceil() as usize;
+
+        (period_min, period_max.max(period_min + 1))
     }
 
-    fn process_audio_data<'a>(this: &Weak<Self>, audio_data: AudioData<'a, ()>) {
+    fn process_audio_data(this: &Weak<Self>, audio_data: &AudioSamples) {
         let this = if let Some(this) = Weak::upgrade(this) {
             this
         } else {
-            // The audio FFT component no longer exists, bail.
+            // The pitch detector component no longer exists, bail.
             return;
         };
 
-        let mut mutable_write = this.mutable.write().unwrap();
-
         let current_samples = if let Some(samples) = audio_data.samples_normalized(this.descriptor.channel) {
             samples
         } else {
@@ -204,91 +1359,147 @@ impl GlobalStateAudioFFT {
             return;
         };
 
-        mutable_write.sample_buffer.extend(current_samples);
+        let (period_min, period_max) = this.get_period_range();
+        let window_len = period_max;
+        let buffer_len = window_len + period_max;
+        let mut mutable_write = this.mutable.write().unwrap();
+        let mutable = &mut *mutable_write;
+
+        mutable.sample_buffer.extend(current_samples);
 
-        let samples_per_frame = Self::get_samples_per_frame();
-        let render_frames_accumulated = mutable_write.sample_buffer.len() / samples_per_frame;
-        let render_frames_over_margin = render_frames_accumulated.saturating_sub(1);
+        // Only keep the most recent samples
+        let samples_to_remove = mutable.sample_buffer.len().saturating_sub(buffer_len);
 
-        // Get rid of old data, if we lost some frames, or if the results are not being requested.
-        if render_frames_over_margin > 0 {
-            // println!("Skipping {} render frames in FFT calculation.", render_frames_over_margin);
+        mutable.sample_buffer.drain(0..samples_to_remove);
 
-            let samples_to_remove = samples_per_frame * render_frames_over_margin;
-            mutable_write.sample_buffer.drain(0..samples_to_remove);
+        if mutable.sample_buffer.len() < buffer_len {
+            return;
         }
 
-        if !mutable_write.next_batch_scheduled.load(Ordering::SeqCst) {
+        let samples = mutable.sample_buffer.make_contiguous();
+        let energy_window = samples[..window_len].iter().map(|sample| sample * sample).sum::<f32>();
+        let rms = (energy_window / window_len as f32).sqrt();
+
+        if rms < Self::SILENCE_RMS {
+            mutable.result.confidence = 0.0;
             return;
         }
 
-        if render_frames_accumulated > 0 {
-            if mutable_write.window.len() != samples_per_frame {
-                mutable_write.window = Arc::new(this.descriptor.window_function.generate_coefficients(samples_per_frame));
-            }
+        // The difference function: d(tau) = sum((x[j] - x[j + tau])^2)
+        //                                  = sum(x[j]^2) + sum(x[j + tau]^2) - 2 * sum(x[j] * x[j + tau])
+        // The cross-correlation in the last term is computed in O(n log n) using the FFT.
+        // The transforms are zero-padded, so that the circular correlation does not wrap around.
+        let fft_len = buffer_len.next_power_of_two();
+        let plan = FFTPlan::get_or_create(&mut mutable.plan, fft_len, WindowFunction::None);
+        let spectrum = &mut mutable.spectrum;
+        let padded = |index: usize, len: usize| Complex::new(if index < len { samples[index] } else { 0.0 }, 0.0);
+
+        plan.buffer.iter_mut()
+            .enumerate()
+            .for_each(|(index, complex)| *complex = padded(index, window_len));
+        spectrum.clear();
+        spectrum.extend((0..fft_len).map(|index| padded(index, buffer_len)));
+
+        {
+            let mut fft = plan.fft.lock().unwrap();
+
+            fft.transform_in_place(&mut plan.buffer, Transform::Fft);
+            fft.transform_in_place(&mut spectrum[..], Transform::Fft);
 
-            let window = mutable_write.window.clone();
-            let current_accumulated_samples = mutable_write.sample_buffer.drain(0..samples_per_frame);
-            let mut analysis_result = Self::perform_analysis(current_accumulated_samples, &window);
-
-            // Dampen the result by mixing it with the result from the previous batch
-            if *this.descriptor.dampening_factor_attack > 0.0 || *this.descriptor.dampening_factor_release > 0.0 {
-                if let Some(previous_result) = mutable_write.result.as_ref() {
-                    let dampening_multiplier_attack = this.descriptor.dampening_factor_attack.powf(
-                        Self::render_frames_to_time_elapsed(render_frames_accumulated)
-                    ).clamp(0.0, 1.0) as f32;
-                    let dampening_multiplier_release = this.descriptor.dampening_factor_release.powf(
-                        Self::render_frames_to_time_elapsed(render_frames_accumulated)
-                    ).clamp(0.0, 1.0) as f32;
-
-                    analysis_result.iter_mut()
-                        .zip(previous_result.frequency_spectrum.iter())
-                        .for_each(move |(current, previous)| {
-                            let dampening_multiplier = if *current > *previous {
-                                dampening_multiplier_attack
-                            } else {
-                                dampening_multiplier_release
-                            };
-
-                            *current = dampening_multiplier * *previous + (1.0 - dampening_multiplier) * *current;
-                        })
+            // The inverse transform of conj(A) * B, computed as conj(FFT(A * conj(B))) / N,
+            // of which only the real part is used.
+            plan.buffer.iter_mut()
+                .zip(spectrum.iter())
+                .for_each(|(a, b)| *a = *a * b.conj());
+            fft.transform_in_place(&mut plan.buffer, Transform::Fft);
+        }
+
+        let mut energy_shifted = energy_window;
+        let difference = &mut mutable.difference;
+
+        difference.clear();
+        difference.push(0.0);
+
+        for period in 1..=period_max {
+            let entering = samples[period + window_len - 1];
+            let leaving = samples[period - 1];
+            let cross_correlation = plan.buffer[period].re / fft_len as f32;
+
+            energy_shifted += entering * entering - leaving * leaving;
+            difference.push((energy_window + energy_shifted - 2.0 * cross_correlation).max(0.0));
+        }
+
+        // The cumulative mean normalized difference function: d'(tau) = d(tau) * tau / sum(d(1..=tau))
+        let mut running_sum = 0.0;
+
+        for period in 1..=period_max {
+            running_sum += difference[period];
+            difference[period] = if running_sum > 0.0 {
+                difference[period] * period as f32 / running_sum
+            } else {
+                1.0
+            };
+        }
+
+        // The first dip below the threshold, or the global minimum, if there is none
+        let threshold = *this.descriptor.threshold as f32;
+        let period = (period_min..period_max)
+            .find(|period| difference[*period] < threshold)
+            .map(|mut period| {
+                while period + 1 < period_max && difference[period + 1] < difference[period] {
+                    period += 1;
                 }
-            }
 
-            let next_batch_number = mutable_write.result.as_ref()
-                .map(|result| result.batch_number + 1).unwrap_or(0);
-            mutable_write.result = Some(FFTResult {
-                batch_number: next_batch_number,
-                frequency_spectrum: Arc::new(analysis_result),
+                period
             });
-            mutable_write.next_batch_scheduled.swap(false, Ordering::SeqCst);
+        let confident = period.is_some();
+        let period = period.unwrap_or_else(|| {
+            (period_min..period_max)
+                .min_by(|a, b| difference[*a].partial_cmp(&difference[*b]).unwrap())
+                .unwrap()
+        });
+
+        // Refine the period by parabolic interpolation
+        let (previous, current, next) = (difference[period - 1], difference[period], difference[period + 1]);
+        let denominator = previous + next - 2.0 * current;
+        let period_refined = if denominator.abs() > f32::EPSILON {
+            period as f32 + (previous - next) / (2.0 * denominator)
+        } else {
+            period as f32
+        };
+        let sample_rate = GlobalStateAudioFFT::get_nyquist_frequency() as f32 * 2.0;
+
+        mutable.result.confidence = (1.0 - current).clamp(0.0, 1.0);
+
+        if confident {
+            mutable.result.frequency = sample_rate / period_refined;
+            mutable.result.note = PitchResult::frequency_to_note(mutable.result.frequency);
         }
     }
 }
 
-impl GlobalStateComponentType for GlobalStateAudioFFT {
-    type Descriptor = GlobalStateAudioFFTDescriptor;
-    type Result = FFTResult;
+impl GlobalStateComponentType for GlobalStatePitchDetector {
+    type Descriptor = GlobalStatePitchDetectorDescriptor;
+    type Result = PitchResult;
 
     fn create(descriptor: &Self::Descriptor) -> Arc<Self> {
-        let audio = Audio::get();
         let result = Arc::new(Self {
             descriptor: descriptor.clone(),
             mutable: Default::default(),
         });
 
-        let audio_output = audio.connect_output(
-            descriptor.mix,
+        let audio_input = AudioInput::new(
+            descriptor.input.clone(),
             {
                 let self_cloned = Arc::downgrade(&result);
 
-                Box::new(move |audio_data| {
+                Arc::new(move |audio_data: &AudioSamples| {
                     Self::process_audio_data(&self_cloned, audio_data);
                 })
             },
         );
 
-        result.mutable.write().unwrap().audio_output = Some(audio_output);
+        result.mutable.write().unwrap().audio_input = Some(audio_input);
 
         result
     }
@@ -296,8 +1507,11 @@ impl GlobalStateComponentType for GlobalStateAudioFFT {
     fn retrieve_result(self: &Arc<Self>) -> Option<Self::Result> {
         let mutable_read = self.mutable.read().unwrap();
 
-        mutable_read.next_batch_scheduled.store(true, Ordering::SeqCst);
-        mutable_read.result.clone()
+        if let Some(audio_input) = mutable_read.audio_input.as_ref() {
+            audio_input.connect();
+        }
+
+        Some(mutable_read.result.clone())
     }
 }
 
@@ -352,42 +1566,77 @@ impl<T: GlobalStateComponentType> GlobalStateComponent<T> {
 
 pub struct GlobalState {
     pub audio_ffts: RwLock<HashMap<GlobalStateAudioFFTDescriptor, GlobalStateComponent<GlobalStateAudioFFT>>>,
+    pub audio_waveforms: RwLock<HashMap<GlobalStateAudioWaveformDescriptor, GlobalStateComponent<GlobalStateAudioWaveform>>>,
+    pub audio_levels: RwLock<HashMap<GlobalStateAudioLevelDescriptor, GlobalStateComponent<GlobalStateAudioLevel>>>,
+    pub beat_detectors: RwLock<HashMap<GlobalStateBeatDetectorDescriptor, GlobalStateComponent<GlobalStateBeatDetector>>>,
+    pub pitch_detectors: RwLock<HashMap<GlobalStatePitchDetectorDescriptor, GlobalStateComponent<GlobalStatePitchDetector>>>,
 }
 
 impl Default for GlobalState {
     fn default() -> Self {
         Self {
             audio_ffts: Default::default(),
+            audio_waveforms: Default::default(),
+            audio_levels: Default::default(),
+            beat_detectors: Default::default(),
+            pitch_detectors: Default::default(),
         }
     }
 }
 
 impl GlobalState {
-    fn request_audio_fft(&self, descriptor: &GlobalStateAudioFFTDescriptor) -> Arc<GlobalStateAudioFFT> {
+    /// Retrieves the component with the given descriptor, or creates it, if it does not exist.
+    fn request_component<T>(
+        components: &RwLock<HashMap<T::Descriptor, GlobalStateComponent<T>>>,
+        descriptor: &T::Descriptor,
+    ) -> Arc<T>
+        where T: GlobalStateComponentType,
+              T::Descriptor: Clone + Hash + Eq,
+    {
         {
-            let audio_ffts_read = self.audio_ffts.read().unwrap();
+            let components_read = components.read().unwrap();
 
-            if let Some(audio_fft) = audio_ffts_read.get(descriptor) {
-                return audio_fft.get_component();
+            if let Some(component) = components_read.get(descriptor) {
+                return component.get_component();
             }
         }
 
         {
-            let mut audio_ffts_write = self.audio_ffts.write().unwrap();
+            let mut components_write = components.write().unwrap();
 
-            if let Some(audio_fft) = audio_ffts_write.get(descriptor) {
-                return audio_fft.get_component();
+            if let Some(component) = components_write.get(descriptor) {
+                return component.get_component();
             }
 
             let component_wrapper = GlobalStateComponent::new(descriptor.clone());
             let component = component_wrapper.get_component();
 
-            audio_ffts_write.retain(|_, component| component.try_get_component().is_some());
-            audio_ffts_write.insert(descriptor.clone(), component_wrapper);
+            components_write.retain(|_, component| component.try_get_component().is_some());
+            components_write.insert(descriptor.clone(), component_wrapper);
 
             component
         }
     }
+
+    fn request_audio_fft(&self, descriptor: &GlobalStateAudioFFTDescriptor) -> Arc<GlobalStateAudioFFT> {
+        Self::request_component(&self.audio_ffts, descriptor)
+    }
+
+    fn request_audio_waveform(&self, descriptor: &GlobalStateAudioWaveformDescriptor) -> Arc<GlobalStateAudioWaveform> {
+        Self::request_component(&self.audio_waveforms, descriptor)
+    }
+
+    fn request_audio_level(&self, descriptor: &GlobalStateAudioLevelDescriptor) -> Arc<GlobalStateAudioLevel> {
+        Self::request_component(&self.audio_levels, descriptor)
+    }
+
+    fn request_beat_detector(&self, descriptor: &GlobalStateBeatDetectorDescriptor) -> Arc<GlobalStateBeatDetector> {
+        Self::request_component(&self.beat_detectors, descriptor)
+    }
+
+    fn request_pitch_detector(&self, descriptor: &GlobalStatePitchDetectorDescriptor) -> Arc<GlobalStatePitchDetector> {
+        Self::request_component(&self.pitch_detectors, descriptor)
+    }
 }
 
 // use crossbeam_channel::{unbounded, Receiver, Sender};
@@ -409,9 +1658,11 @@ struct Data {
 
     property_shader: PropertyDescriptor<PropertyDescriptorSpecializationPath>,
     property_shader_reload: PropertyDescriptor<PropertyDescriptorSpecializationButton>,
+    property_shader_autoreload: PropertyDescriptor<PropertyDescriptorSpecializationBool>,
     property_message: PropertyDescriptor<PropertyDescriptorSpecializationString>,
     property_message_display: bool,
 
+    shader_watcher: Option<ShaderWatcher>,
     settings_update_requested: Arc<AtomicBool>,
     shown: bool,
     enabled: Arc<AtomicBool>,
@@ -457,7 +1708,7 @@ impl Data {
                 description: CString::new("The shader to use.").unwrap(),
                 specialization: PropertyDescriptorSpecializationPath {
                     path_type: PathType::File,
-                    filter: CString::from(cstr!("*.hlsl *.glsl *.frag *.fragment ;; All File Types | *.*")),
+                    filter: CString::from(cstr!("*.hlsl *.glsl *.frag *.fragment *.shadertoy *.fs ;; All File Types | *.*")),
                     default_path: CString::from(cstr!("")),
                 },
             },
@@ -474,6 +1725,11 @@ impl Data {
                     }),
                 )
             },
+            property_shader_autoreload: PropertyDescriptor {
+                name: CString::new("builtin_ui_shader_autoreload").unwrap(),
+                description: CString::new("Reload Shader Automatically").unwrap(),
+                specialization: PropertyDescriptorSpecializationBool {},
+            },
             property_message: PropertyDescriptor {
                 name: CString::new("builtin_ui_message").unwrap(),
                 description: CString::new("").unwrap(),
@@ -482,11 +1738,35 @@ impl Data {
                 }
             },
             property_message_display: false,
+            shader_watcher: None,
             settings_update_requested,
             shown: false,
             enabled,
         }
     }
+
+    /// Watches the shader file and the files included by the current effect for changes,
+    /// if automatic reloading is enabled.
+    fn update_shader_watcher(&mut self, shader_path: &Path, autoreload: bool) {
+        if !autoreload {
+            self.shader_watcher = None;
+            return;
+        }
+
+        let mut paths = vec![shader_path.to_path_buf()];
+
+        if let Some(effect) = self.effect.as_ref() {
+            paths.extend(effect.includes.iter().map(|included| included.path.clone()));
+        }
+
+        if let Some(shader_watcher) = self.shader_watcher.as_ref() {
+            if shader_watcher.is_watching(&paths) {
+                return;
+            }
+        }
+
+        self.shader_watcher = Some(ShaderWatcher::new(paths));
+    }
 }
 
 impl Drop for Data {
@@ -529,6 +1809,7 @@ impl GetPropertiesSource<Data> for ShaderFilterPlus {
 
         properties.add_property(&data.property_shader);
         properties.add_property(&data.property_shader_reload);
+        properties.add_property(&data.property_shader_autoreload);
 
         if data.property_message_display {
             properties.add_property(&data.property_message);
@@ -609,6 +1890,11 @@ impl VideoTickSource<Data> for ShaderFilterPlus {
                 data.source.get_base_height() as i32,
             ]);
 
+            if let Some(date) = params.date.as_mut() {
+                date.prepare_value(get_utc_date());
+            }
+
+            params.apply_hotkeys(settings);
             params.custom.prepare_values();
 
             {
@@ -617,6 +1903,12 @@ impl VideoTickSource<Data> for ShaderFilterPlus {
             }
         }
 
+        if let Some(shader_watcher) = data.shader_watcher.as_mut() {
+            if shader_watcher.poll_changed() {
+                data.settings_update_requested.store(true, Ordering::SeqCst);
+            }
+        }
+
         if data.settings_update_requested.compare_and_swap(true, false, Ordering::SeqCst) {
             data.source.update_source_settings(settings);
         }
@@ -657,19 +1949,65 @@ impl VideoRenderSource<Data> for ShaderFilterPlus {
             return;
         };
 
+        prepared_effect.params.render_textures(graphics_context);
+
+        if let Some(feedback) = prepared_effect.feedback.as_mut() {
+            feedback.prepare([cx, cy]);
+
+            if let Some(previous_output) = prepared_effect.params.previous_output.as_mut() {
+                previous_output.set_texture(feedback.texture_previous());
+            }
+        }
+
+        let filter = source.as_ptr();
         let effect = &mut prepared_effect.effect.as_enabled_mut(graphics_context);
         let params = &mut prepared_effect.params;
+        let passes = &mut prepared_effect.passes;
+        // The filter input is rendered once, then each pass is drawn from it.
+        let mut process_filter = || {
+            source.process_filter(
+                effect,
+                (cx, cy),
+                ColorFormatKind::RGBA,
+                GraphicsAllowDirectRendering::NoDirectRendering,
+                |context, effect| {
+                    // Outputs of passes are only available to the passes declared after them,
+                    // except for the outputs of persistent passes from the previous frame.
+                    params.passes.iter_mut()
+                        .zip(passes.iter_mut())
+                        .for_each(|(param, pass)| param.set_texture(pass.prepare([cx, cy])));
+
+                    for (pass_index, pass) in passes.iter_mut().enumerate() {
+                        let dimensions = pass.get_dimensions([cx, cy]);
+
+                        params.pass_index.prepare_value(pass_index as i32);
+                        params.pass_index.stage_value(context.graphics());
+
+                        let rendered = pass.render(dimensions, context.graphics(), || {
+                            params.assign_values(&context);
+                            draw_filter_input(filter, effect.as_ptr(), dimensions, context.graphics());
+                        });
+
+                        if rendered {
+                            params.passes[pass_index].set_texture(pass.texture());
+                        }
+                    }
 
-        source.process_filter(
-            effect,
-            (cx, cy),
-            ColorFormatKind::RGBA,
-            GraphicsAllowDirectRendering::NoDirectRendering,
-            |context, _effect| {
-                params.assign_values(&context);
-                // image.set_next_sampler(context, sampler);
-            },
-        );
+                    // The final pass calls `render` and is drawn by `process_filter`
+                    params.pass_index.prepare_value(-1);
+                    params.pass_index.stage_value(context.graphics());
+                    params.assign_values(&context);
+                    // image.set_next_sampler(context, sampler);
+                },
+            );
+        };
+
+        if let Some(feedback) = prepared_effect.feedback.as_mut() {
+            // Render into a texture first, so that the output is available in the next frame
+            feedback.render([cx, cy], graphics_context, || process_filter());
+        } else {
+            process_filter();
+        }
     }
 }
 
@@ -688,11 +2026,15 @@ impl UpdateSource<Data> for ShaderFilterPlus {
             let data = data.as_mut().ok_or_else(|| "Could not access the data.")?;
 
             let shader_path = settings.get_property_value(&data.property_shader, &PathBuf::new());
+            let shader_autoreload = settings.get_property_value(&data.property_shader_autoreload, &false);
 
             if shader_path.as_path().as_os_str().is_empty() {
+                data.shader_watcher = None;
                 throw!("Please specify the shader source file.");
             }
 
+            data.update_shader_watcher(&shader_path, shader_autoreload);
+
             let mut shader_file = File::open(&shader_path)
                 .map_err(|_| {
                     if let Some(effect) = data.effect.take() {
@@ -709,14 +2051,27 @@ impl UpdateSource<Data> for ShaderFilterPlus {
                 shader_file.read_to_string(&mut shader_source).expect("Could not read the shader at the given path.");
                 shader_source
             };
-            let old_shader_source = data.effect.as_ref().map(|old_effect| {
-                old_effect.shader_source.clone()
-            });
+            let shader_unchanged = data.effect.as_ref().map(|old_effect| {
+                old_effect.shader_source == shader_source && !old_effect.includes_changed()
+            }).unwrap_or(false);
 
-            if old_shader_source.is_some() && old_shader_source.unwrap() == shader_source {
+            if shader_unchanged {
                 // Only update the params, if the shader stayed the same
                 let effect = data.effect.as_mut().unwrap();
                 effect.params.reload_settings(&mut settings);
+
+                if let Some(load_error) = effect.params.custom.load_error() {
+                    throw!(load_error.to_string());
+                }
+
+                // Only refresh the properties to hide a previous message, as it would interrupt editing
+                if data.property_message_display {
+                    data.property_message_display = false;
+
+                    settings.set_property_value(&data.property_message, CString::new("").unwrap());
+                    data.source.update_source_properties();
+                }
+
                 return;
             }
 
@@ -724,7 +2079,13 @@ impl UpdateSource<Data> for ShaderFilterPlus {
 
             // If shader source changed, create a new effect and request to update properties
             let graphics_context = GraphicsContext::enter().unwrap();
-            let (effect, preprocess_result) = PreparedEffect::create_effect(&shader_path, &shader_source, &graphics_context)?;
+            let (effect, preprocess_result) = if isf::is_isf(&shader_path, &shader_source) {
+                let (isf_source, line_offset) = isf::translate(&shader_source)?;
+
+                PreparedEffect::create_effect(&shader_path, &isf_source, line_offset, &graphics_context)?
+            } else {
+                PreparedEffect::create_effect(&shader_path, &shader_source, 0, &graphics_context)?
+            };
             let mut builtin_param_names = vec!["ViewProj", "image"];
 
             macro_rules! builtin_effect {
@@ -754,12 +2115,61 @@ impl UpdateSource<Data> for ShaderFilterPlus {
                 elapsed_time_since_enabled: builtin_effect!("builtin_elapsed_time_since_enabled"),
                 elapsed_time_since_enabled_previous: builtin_effect!("builtin_elapsed_time_since_enabled_previous"),
                 uv_size: builtin_effect!("builtin_uv_size"),
+                date: None,
+                pass_index: builtin_effect!("builtin_pass_index"),
+                previous_output: None,
+                passes: Vec::new(),
                 custom: Default::default(),
             };
 
+            // Only keep the previous output around, if it is used by the shader
+            if let Some(param) = effect.get_param_by_name(cstr!("builtin_texture_previous_output")) {
+                builtin_param_names.push("builtin_texture_previous_output");
+                params.previous_output = Some(EffectParamRawTexture::new(
+                    param.downcast()
+                        .ok_or_else(|| {
+                            format!("Builtin field `{}` must be of type `{}`", "builtin_texture_previous_output", "texture2d")
+                        })?
+                        .disable()
+                ));
+            }
+
+            // Only compute the date, if it is used by the shader
+            if let Some(param) = effect.get_param_by_name(cstr!("builtin_date")) {
+                builtin_param_names.push("builtin_date");
+                params.date = Some(EffectParam::new(
+                    param.downcast()
+                        .ok_or_else(|| {
+                            format!("Builtin field `{}` must be of type `{}`", "builtin_date", "float4")
+                        })?
+                        .disable()
+                ));
+            }
+
+            // Outputs of intermediate passes, generated from `#pragma shaderfilter pass`
+            let mut pass_param_names = Vec::new();
+
+            for pass in preprocess_result.passes() {
+                let param_name = format!("builtin_texture_pass_{}", pass.name);
+                let param_name_c = CString::new(param_name.clone()).unwrap();
+                let param = effect.get_param_by_name(param_name_c.as_c_str())
+                    .ok_or_else(|| {
+                        format!("Could not access built in effect parameter `{}`.", &param_name)
+                    })?
+                    .downcast()
+                    .ok_or_else(|| {
+                        format!("Incompatible effect parameter type `{}`.", &param_name)
+                    })?
+                    .disable();
+
+                params.passes.push(EffectParamRawTexture::new(param));
+                pass_param_names.push(param_name);
+            }
+
             let custom_params = effect.params_iter()
                 .filter(|item| {
                     !builtin_param_names.contains(&item.name())
+                        && !pass_param_names.iter().any(|name| name == item.name())
                 })
                 .enumerate()
                 .map(|(index, param)| {
@@ -767,11 +2177,21 @@ impl UpdateSource<Data> for ShaderFilterPlus {
                 })
                 .collect::<HashMap<_, _>>();
 
-            params.custom = EffectParamsCustom::from(custom_params, settings, &preprocess_result)?;
+            params.custom = EffectParamsCustom::from(custom_params, data.source.as_ptr(), settings, &preprocess_result)?;
+            params.custom.register_hotkeys(
+                data.source.as_ptr(),
+                data.effect.as_mut().map(|old_effect| &mut old_effect.params.custom),
+            );
 
             let effect = PreparedEffect {
                 effect: effect.disable(),
                 shader_source: shader_source.clone(),
+                includes: preprocess_result.includes().to_vec(),
+                feedback: params.previous_output.as_ref()
+                    .map(|_| FeedbackRenderTargets::new(&graphics_context)),
+                passes: preprocess_result.passes().iter()
+                    .map(|descriptor| RenderPass::new(descriptor.clone(), &graphics_context))
+                    .collect(),
                 params,
             };
 
@@ -782,6 +2202,13 @@ impl UpdateSource<Data> for ShaderFilterPlus {
                 old_effect.enable_and_drop(&graphics_context);
             }
 
+            // Watch the files included by the new effect
+            data.update_shader_watcher(&shader_path, shader_autoreload);
+
+            if let Some(load_error) = data.effect.as_ref().and_then(|effect| effect.params.custom.load_error()) {
+                throw!(load_error.to_string());
+            }
+
             data.property_message_display = false;
 
             settings.set_property_value(&data.property_message, CString::new("").unwrap());
@@ -863,3 +2290,99 @@ impl Module for ShaderFilterPlus {
 }
 
 obs_register_module!(ShaderFilterPlus);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fft_plan_is_reused_between_batches() {
+        let fft_size = 1024;
+        let samples = (0..fft_size).map(|index| (index as f32 * 0.1).sin()).collect::<Vec<_>>();
+        let mut plan = None;
+
+        let first = FFTPlan::get_or_create(&mut plan, fft_size, WindowFunction::Hanning);
+        GlobalStateAudioFFT::perform_analysis(samples.iter().copied(), first, FFTOutput::Amplitude);
+        let first_spectrum = first.spectrum.clone();
+        let first_buffers = (
+            first.window.as_ptr(),
+            first.buffer.as_ptr(),
+            first.spectrum.as_ptr(),
+            first.spectrum.capacity(),
+        );
+
+        let second = FFTPlan::get_or_create(&mut plan, fft_size, WindowFunction::Hanning);
+        GlobalStateAudioFFT::perform_analysis(samples.iter().copied(), second, FFTOutput::Amplitude);
+        let second_buffers = (
+            second.window.as_ptr(),
+            second.buffer.as_ptr(),
+            second.spectrum.as_ptr(),
+            second.spectrum.capacity(),
+        );
+
+        // The plan was not rebuilt and its buffers were not reallocated
+        assert_eq!(first_buffers, second_buffers);
+        assert_eq!(second.spectrum.len(), fft_size / 2);
+        assert_eq!(second.spectrum, first_spectrum);
+    }
+
+    #[test]
+    fn fft_plan_is_rebuilt_when_the_size_changes() {
+        let mut plan = None;
+        let first_window = FFTPlan::get_or_create(&mut plan, 1024, WindowFunction::Hanning).window.clone();
+        let second = FFTPlan::get_or_create(&mut plan, 2048, WindowFunction::Hanning);
+
+        assert_eq!(first_window.len(), 1024);
+        assert_eq!(second.len(), 2048);
+        assert_eq!(second.window, WindowFunction::Hanning.generate_coefficients(2048));
+        assert_eq!(second.buffer.len(), 2048);
+    }
+
+    #[test]
+    fn analyzer_reuses_buffers_between_batches() {
+        let fft_size = 1024;
+        let samples = (0..fft_size).map(|index| (index as f32 * 0.1).sin()).collect::<Vec<_>>();
+        let analyzer = GlobalStateAudioFFT {
+            descriptor: GlobalStateAudioFFTDescriptor::new(
+                AudioInputDescriptor::Mix(0),
+                0,
+                0.0,
+                0.0,
+                WindowFunction::Hanning,
+                fft_size,
+                0.0,
+                FFTOutput::Amplitude,
+                None,
+            ),
+            mutable: Default::default(),
+        };
+        let buffers = || {
+            let mutable = analyzer.mutable.read().unwrap();
+            let plan = mutable.plan.as_ref().unwrap();
+            let result = mutable.result.as_ref().unwrap();
+
+            (
+                result.batch_number,
+                (plan.window.as_ptr(), plan.buffer.as_ptr(), plan.spectrum.as_ptr()),
+                result.frequency_spectrum.as_ptr(),
+                result.frequency_spectrum.len(),
+            )
+        };
+
+        analyzer.process_samples(&samples, 48000.0);
+        let (first_batch, first_plan, first_result, first_len) = buffers();
+
+        analyzer.mutable.read().unwrap().next_batch_scheduled.store(true, Ordering::SeqCst);
+        analyzer.process_samples(&samples, 48000.0);
+        let (second_batch, second_plan, second_result, second_len) = buffers();
+
+        // The second batch was analyzed without rebuilding the plan or reallocating the result,
+        // as the previous result is no longer referenced
+        assert_eq!(first_batch, 0);
+        assert_eq!(second_batch, 1);
+        assert_eq!(first_plan, second_plan);
+        assert_eq!(first_result, second_result);
+        assert_eq!(first_len, fft_size / 2);
+        assert_eq!(second_len, fft_size / 2);
+    }
+}
@@ -1,12 +1,91 @@
 use std::str::FromStr;
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use regex::{Regex, Replacer, Captures};
 use anyhow::Result;
 
+/// A file pulled into the shader via `#pragma shaderfilter include`.
+#[derive(Clone, Debug)]
+pub struct IncludedSource {
+    pub path: PathBuf,
+    pub source: String,
+}
+
+/// An intermediate render pass declared using `#pragma shaderfilter pass`.
+#[derive(Clone, Debug)]
+pub struct PassDescriptor {
+    pub name: String,
+    /// The resolution of the pass relative to the resolution of the source.
+    pub scale: f32,
+    /// Whether the output of the previous frame is available to the pass itself and the passes before it.
+    pub persistent: bool,
+}
+
+/// Maps lines of the preprocessed shader back to the files they originate from.
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    files: Vec<PathBuf>,
+    /// The index of the file in `files` and the line number within that file,
+    /// for each line of the preprocessed shader.
+    lines: Vec<(usize, usize)>,
+}
+
+impl SourceMap {
+    /// The number of lines of the preprocessed shader.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Finds the original file and line of the specified line of the preprocessed shader.
+    /// Line numbers are 1-based.
+    pub fn resolve(&self, line: usize) -> Option<(&Path, usize)> {
+        let &(file_index, file_line) = self.lines.get(line.checked_sub(1)?)?;
+
+        Some((&self.files[file_index], file_line))
+    }
+
+    /// Moves the lines of the preprocessed file up by `line_offset`, for sources with lines inserted
+    /// before the original source, such as translated ISF shaders. The inserted lines map to the first line.
+    fn remove_line_offset(&mut self, line_offset: usize) {
+        for (file_index, file_line) in &mut self.lines {
+            if *file_index == 0 {
+                *file_line = file_line.saturating_sub(line_offset).max(1);
+            }
+        }
+    }
+
+    fn add_file(&mut self, path: PathBuf) -> usize {
+        self.files.push(path);
+        self.files.len() - 1
+    }
+}
+
+/// The conventions the shader is written in, which determine the effect template it is wrapped in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderDialect {
+    /// A shader implementing `render`.
+    Native,
+    /// A shader implementing Shadertoy's `mainImage`.
+    Shadertoy,
+    /// A shader in the Interactive Shader Format, translated by `isf::translate`.
+    Isf,
+}
+
+impl Default for ShaderDialect {
+    fn default() -> Self {
+        ShaderDialect::Native
+    }
+}
+
 #[derive(Default)]
 pub struct PreprocessResult {
+    source_path: PathBuf,
     map: HashMap<String, String>,
+    includes: Vec<IncludedSource>,
+    passes: Vec<PassDescriptor>,
+    dialect: ShaderDialect,
+    source_map: SourceMap,
 }
 
 impl PreprocessResult {
@@ -36,6 +115,35 @@ impl PreprocessResult {
             })
             .and_then(|result| result)
     }
+
+    /// The path to the preprocessed shader file.
+    pub fn source_path(&self) -> &Path {
+        &self.source_path
+    }
+
+    /// The directory containing the preprocessed shader file,
+    /// used to resolve relative paths.
+    pub fn source_directory(&self) -> &Path {
+        self.source_path.parent().unwrap_or_else(|| Path::new(""))
+    }
+
+    /// The files included by the shader, directly or transitively.
+    pub fn includes(&self) -> &[IncludedSource] {
+        &self.includes
+    }
+
+    /// The intermediate render passes, in the order of declaration.
+    pub fn passes(&self) -> &[PassDescriptor] {
+        &self.passes
+    }
+
+    pub fn dialect(&self) -> ShaderDialect {
+        self.dialect
+    }
+
+    pub fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
 }
 
 impl<'a> Replacer for &'a mut PreprocessResult {
@@ -56,12 +164,228 @@ impl<'a> Replacer for &'a mut PreprocessResult {
     }
 }
 
-pub fn preprocess(source: &str) -> (PreprocessResult, Cow<str>) {
-    let mut result = PreprocessResult::default();
+/// Replaces `#pragma shaderfilter include "<path>"` lines with the contents of the referenced files.
+/// Paths are resolved relative to the file containing the directive.
+/// Each file is only included once, cyclic includes result in an error.
+fn expand_includes(
+    source: &str,
+    source_path: &Path,
+    include_stack: &mut Vec<PathBuf>,
+    result: &mut PreprocessResult,
+) -> Result<String, Cow<'static, str>> {
+    // Matches on macros:
+    // #pragma shaderfilter include "<path>"
+    let pattern = Regex::new(r#"^\s*#pragma\s+shaderfilter\s+include\s+"(?P<path>[^"]+)"\s*$"#).unwrap();
+    let file_index = result.source_map.add_file(source_path.to_path_buf());
+    let mut expanded = String::with_capacity(source.len());
+
+    for (line_index, line) in source.lines().enumerate() {
+        let captures = if let Some(captures) = pattern.captures(line) {
+            captures
+        } else {
+            expanded.push_str(line);
+            expanded.push('\n');
+            result.source_map.lines.push((file_index, line_index + 1));
+            continue;
+        };
+        let include_path = source_path.parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(captures.name("path").unwrap().as_str());
+        let include_path = include_path.canonicalize()
+            .map_err(|_| {
+                format!("Included file not found at path {:?}, included from {:?}.", &include_path, source_path)
+            })?;
+
+        if include_stack.contains(&include_path) {
+            let cycle = include_stack.iter()
+                .chain(std::iter::once(&include_path))
+                .map(|path| format!("{:?}", path))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+
+            throw!(format!("Cyclic include detected: {}", cycle));
+        }
+
+        if result.includes.iter().any(|included| included.path == include_path) {
+            // Already included, skip to avoid redefinitions.
+            continue;
+        }
+
+        let include_source = std::fs::read_to_string(&include_path)
+            .map_err(|_| format!("Could not read the included file at path {:?}.", &include_path))?;
+
+        include_stack.push(include_path.clone());
+        let include_expanded = expand_includes(&include_source, &include_path, include_stack, result)?;
+        include_stack.pop();
+
+        result.includes.push(IncludedSource {
+            path: include_path,
+            source: include_source,
+        });
+        expanded.push_str(&include_expanded);
+    }
+
+    Ok(expanded)
+}
+
+/// Preprocesses the shader located at `source_path`, resolving includes and
+/// collecting all `#pragma shaderfilter set` definitions.
+/// `line_offset` is the number of lines generated before the contents of the file, see `SourceMap::remove_line_offset`.
+pub fn preprocess(source: &str, source_path: &Path, line_offset: usize) -> Result<(PreprocessResult, String), Cow<'static, str>> {
+    let mut result = PreprocessResult {
+        source_path: source_path.to_path_buf(),
+        ..Default::default()
+    };
+    let mut include_stack = vec![
+        source_path.canonicalize().unwrap_or_else(|_| source_path.to_path_buf()),
+    ];
+    let source = expand_includes(source, source_path, &mut include_stack, &mut result)?;
+
+    result.source_map.remove_line_offset(line_offset);
     // Matches on macros:
     // #pragma shaderfilter <identifier> <value>
-    let pattern = Regex::new(r"(?m)^\s*#pragma\s+shaderfilter\s+set\s+(?P<identifier>\w+)\s+(?P<value>[^\s].*?)\s*$").unwrap();
-    let string = pattern.replace_all(source, &mut result);
+    // Surrounding whitespace is restricted to a single line, so that the line numbers of the
+    // source map stay valid after the macros are removed.
+    let pattern = Regex::new(r"(?m)^[ \t]*#pragma[ \t]+shaderfilter[ \t]+set[ \t]+(?P<identifier>\w+)[ \t]+(?P<value>[^\s].*?)[ \t]*$").unwrap();
+    let string = pattern.replace_all(&source, &mut result).into_owned();
+    // Matches on macros:
+    // #pragma shaderfilter pass <name> [<scale>] [persistent]
+    let pattern_pass = Regex::new(r"(?m)^[ \t]*#pragma[ \t]+shaderfilter[ \t]+pass[ \t]+(?P<name>\w+)(?:[ \t]+(?P<scale>[^\s]+))??(?:[ \t]+(?P<persistent>persistent))?[ \t]*$").unwrap();
+    let mut pass_definitions: Vec<(String, Option<String>, bool)> = Vec::new();
+    let string = pattern_pass.replace_all(&string, |captures: &Captures| {
+        pass_definitions.push((
+            captures.name("name").unwrap().as_str().to_string(),
+            captures.name("scale").map(|scale| scale.as_str().to_string()),
+            captures.name("persistent").is_some(),
+        ));
+        ""
+    }).into_owned();
+
+    if source_path.extension().map(|extension| extension == "shadertoy").unwrap_or(false) {
+        result.dialect = ShaderDialect::Shadertoy;
+    }
+
+    // Matches on macros:
+    // #pragma shaderfilter shadertoy
+    // #pragma shaderfilter isf
+    let pattern_dialect = Regex::new(r"(?m)^[ \t]*#pragma[ \t]+shaderfilter[ \t]+(?P<dialect>shadertoy|isf)[ \t]*$").unwrap();
+    let string = pattern_dialect.replace_all(&string, |captures: &Captures| {
+        result.dialect = match captures.name("dialect").unwrap().as_str() {
+            "shadertoy" => ShaderDialect::Shadertoy,
+            _ => ShaderDialect::Isf,
+        };
+        ""
+    }).into_owned();
+
+    for (name, scale, persistent) in pass_definitions {
+        let scale = if let Some(scale) = scale {
+            scale.parse::<f32>().ok()
+                .filter(|scale| *scale > 0.0)
+                .ok_or_else(|| format!("Invalid resolution scale `{}` of pass `{}`.", scale, name))?
+        } else {
+            1.0
+        };
+
+        if result.passes.iter().any(|pass| pass.name == name) {
+            throw!(format!("Pass `{}` is declared multiple times.", name));
+        }
+
+        result.passes.push(PassDescriptor { name, scale, persistent });
+    }
+
+    Ok((result, string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes the files into a new temporary directory and returns its path.
+    fn write_files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory = std::env::temp_dir()
+            .join(format!("obs-shaderfilter-{}-{}", name, std::process::id()));
+
+        std::fs::create_dir_all(&directory).unwrap();
+
+        for (file_name, contents) in files {
+            std::fs::write(directory.join(file_name), contents).unwrap();
+        }
+
+        directory.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn includes_are_expanded_once() {
+        let directory = write_files("include-once", &[
+            ("main.effect", "#pragma shaderfilter include \"a.hlsl\"\n#pragma shaderfilter include \"b.hlsl\"\nmain\n"),
+            ("a.hlsl", "#pragma shaderfilter include \"common.hlsl\"\na\n"),
+            ("b.hlsl", "#pragma shaderfilter include \"common.hlsl\"\nb\n"),
+            ("common.hlsl", "common\n"),
+        ]);
+        let source_path = directory.join("main.effect");
+        let source = std::fs::read_to_string(&source_path).unwrap();
+        let (result, expanded) = preprocess(&source, &source_path, 0).unwrap();
+
+        assert_eq!(expanded, "common\na\nb\nmain\n");
+        assert_eq!(
+            result.includes().iter().map(|included| included.path.clone()).collect::<Vec<_>>(),
+            vec![directory.join("common.hlsl"), directory.join("a.hlsl"), directory.join("b.hlsl")],
+        );
+    }
+
+    #[test]
+    fn cyclic_includes_are_rejected() {
+        let directory = write_files("include-cycle", &[
+            ("main.effect", "#pragma shaderfilter include \"a.hlsl\"\n"),
+            ("a.hlsl", "#pragma shaderfilter include \"b.hlsl\"\n"),
+            ("b.hlsl", "#pragma shaderfilter include \"a.hlsl\"\n"),
+        ]);
+        let source_path = directory.join("main.effect");
+        let source = std::fs::read_to_string(&source_path).unwrap();
+        let error = preprocess(&source, &source_path, 0).err().unwrap();
+
+        assert!(error.starts_with("Cyclic include detected"), "{}", error);
+        assert!(error.contains("b.hlsl"), "{}", error);
+    }
+
+    #[test]
+    fn missing_includes_are_rejected() {
+        let directory = write_files("include-missing", &[
+            ("main.effect", "#pragma shaderfilter include \"missing.hlsl\"\n"),
+        ]);
+        let source_path = directory.join("main.effect");
+        let source = std::fs::read_to_string(&source_path).unwrap();
+        let error = preprocess(&source, &source_path, 0).err().unwrap();
+
+        assert!(error.starts_with("Included file not found"), "{}", error);
+    }
+    #[test]
+    fn source_map_resolves_included_lines() {
+        let directory = write_files("source-map", &[
+            ("main.effect", "first\n#pragma shaderfilter include \"a.hlsl\"\nlast\n"),
+            ("a.hlsl", "a1\na2\n"),
+        ]);
+        let source_path = directory.join("main.effect");
+        let source = std::fs::read_to_string(&source_path).unwrap();
+        let (result, _) = preprocess(&source, &source_path, 0).unwrap();
+        let source_map = result.source_map();
+        let include_path = directory.join("a.hlsl");
+
+        assert_eq!(source_map.len(), 4);
+        assert_eq!(source_map.resolve(0), None);
+        assert_eq!(source_map.resolve(1), Some((source_path.as_path(), 1)));
+        assert_eq!(source_map.resolve(2), Some((include_path.as_path(), 1)));
+        assert_eq!(source_map.resolve(3), Some((include_path.as_path(), 2)));
+        assert_eq!(source_map.resolve(4), Some((source_path.as_path(), 3)));
+        assert_eq!(source_map.resolve(5), None);
+
+        // Generated lines map to the first line, included files are not affected
+        let (result, _) = preprocess(&source, &source_path, 1).unwrap();
+        let source_map = result.source_map();
+
+        assert_eq!(source_map.resolve(1), Some((source_path.as_path(), 1)));
+        assert_eq!(source_map.resolve(2), Some((include_path.as_path(), 1)));
+        assert_eq!(source_map.resolve(4), Some((source_path.as_path(), 2)));
+    }
 
-    (result, string)
 }
@@ -0,0 +1,327 @@
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use obs_wrapper::{
+    obs_sys::*,
+    source::*,
+};
+
+unsafe extern "C" fn enum_source_names(param: *mut c_void, source: *mut obs_source_t) -> bool {
+    let (output_flags, excluded, names) = &mut *(param as *mut (u32, *mut obs_source_t, Vec<CString>));
+
+    if obs_source_get_output_flags(source) & *output_flags != 0 && !renders_source(source, *excluded) {
+        let name = obs_source_get_name(source);
+
+        if !name.is_null() {
+            names.push(CStr::from_ptr(name).to_owned());
+        }
+    }
+
+    true
+}
+
+/// Returns `true`, if rendering `source` also renders `target`, that is, if it is the same source
+/// or a scene containing it.
+unsafe fn renders_source(source: *mut obs_source_t, target: *mut obs_source_t) -> bool {
+    if target.is_null() {
+        return false;
+    }
+
+    if source == target {
+        return true;
+    }
+
+    let scene = obs_scene_from_source(source);
+    let target_name = obs_source_get_name(target);
+
+    !scene.is_null() && !target_name.is_null() && !obs_scene_find_source_recursive(scene, target_name).is_null()
+}
+
+/// Lists the names of all scenes and sources with any of the given output flags.
+/// Scenes and sources which would render the `excluded` source are omitted.
+pub fn get_source_names(output_flags: u32, excluded: *mut obs_source_t) -> Vec<CString> {
+    let mut param: (u32, *mut obs_source_t, Vec<CString>) = (output_flags, excluded, Vec::new());
+    let param_ptr = &mut param as *mut (u32, *mut obs_source_t, Vec<CString>) as *mut c_void;
+
+    unsafe {
+        obs_enum_scenes(Some(enum_source_names), param_ptr);
+        obs_enum_sources(Some(enum_source_names), param_ptr);
+    }
+
+    let (_, _, mut names) = param;
+
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// A drop-down list of scenes and sources with any of the given output flags, identified by their name.
+/// The list is populated each time the properties are displayed.
+#[derive(Clone, Debug)]
+pub struct PropertyDescriptorSpecializationSource {
+    /// The output flags, such as `OBS_SOURCE_VIDEO` or `OBS_SOURCE_AUDIO`.
+    pub output_flags: u32,
+    /// The name of the item with an empty value, displayed first.
+    pub none_name: CString,
+    /// The filter showing the list, or null. Its parent, and scenes containing the parent, are
+    /// omitted, as they cannot be rendered from within the filter.
+    pub filter: *mut obs_source_t,
+}
+
+impl PropertyDescriptorSpecializationSource {
+    pub fn video(filter: *mut obs_source_t) -> Self {
+        Self {
+            output_flags: OBS_SOURCE_VIDEO,
+            none_name: CString::new("None").unwrap(),
+            filter,
+        }
+    }
+
+    pub fn audio() -> Self {
+        Self {
+            output_flags: OBS_SOURCE_AUDIO,
+            none_name: CString::new("None (use Mix/Track)").unwrap(),
+            filter: std::ptr::null_mut(),
+        }
+    }
+}
+
+impl PropertyDescriptorSpecialization for PropertyDescriptorSpecializationSource {
+    unsafe fn create_property(
+        &self,
+        name: *const c_char,
+        description: *const c_char,
+        properties: *mut obs_properties_t,
+    ) -> *mut obs_property_t {
+        let property = obs_properties_add_list(
+            properties,
+            name,
+            description,
+            obs_combo_type_OBS_COMBO_TYPE_LIST,
+            obs_combo_format_OBS_COMBO_FORMAT_STRING,
+        );
+
+        let none_value = CString::default();
+
+        obs_property_list_add_string(property, self.none_name.as_ptr(), none_value.as_ptr());
+
+        let parent = if self.filter.is_null() {
+            std::ptr::null_mut()
+        } else {
+            obs_filter_get_parent(self.filter)
+        };
+
+        for source_name in get_source_names(self.output_flags, parent) {
+            obs_property_list_add_string(property, source_name.as_ptr(), source_name.as_ptr());
+        }
+
+        property
+    }
+}
+
+impl ValuePropertyDescriptorSpecialization for PropertyDescriptorSpecializationSource {
+    type ValueType = String;
+
+    unsafe fn get_property_value(&self, name: *const c_char, settings: *mut obs_data_t) -> Self::ValueType {
+        CStr::from_ptr(obs_data_get_string(settings, name)).to_string_lossy().into_owned()
+    }
+
+    unsafe fn set_property_value(&self, name: *const c_char, settings: *mut obs_data_t, value: Self::ValueType) {
+        let value = CString::new(value).unwrap_or_default();
+
+        obs_data_set_string(settings, name, value.as_ptr());
+    }
+
+    unsafe fn set_property_default_value(&self, name: *const c_char, settings: *mut obs_data_t, value: Self::ValueType) {
+        let value = CString::new(value).unwrap_or_default();
+
+        obs_data_set_default_string(settings, name, value.as_ptr());
+    }
+}
+
+/// A drop-down list of predefined string values.
+#[derive(Clone, Debug)]
+pub struct PropertyDescriptorSpecializationListString {
+    /// The names of the items displayed in the UI, paired with the corresponding values.
+    pub items: Vec<(CString, CString)>,
+}
+
+impl PropertyDescriptorSpecializationListString {
+    /// Creates a list of items whose displayed names are equal to their values.
+    pub fn from_names(names: &[&str]) -> Self {
+        Self {
+            items: names.iter()
+                .map(|name| (CString::new(*name).unwrap(), CString::new(*name).unwrap()))
+                .collect(),
+        }
+    }
+}
+
+impl PropertyDescriptorSpecialization for PropertyDescriptorSpecializationListString {
+    unsafe fn create_property(
+        &self,
+        name: *const c_char,
+        description: *const c_char,
+        properties: *mut obs_properties_t,
+    ) -> *mut obs_property_t {
+        let property = obs_properties_add_list(
+            properties,
+            name,
+            description,
+            obs_combo_type_OBS_COMBO_TYPE_LIST,
+            obs_combo_format_OBS_COMBO_FORMAT_STRING,
+        );
+
+        for (item_name, item_value) in &self.items {
+            obs_property_list_add_string(property, item_name.as_ptr(), item_value.as_ptr());
+        }
+
+        property
+    }
+}
+
+impl ValuePropertyDescriptorSpecialization for PropertyDescriptorSpecializationListString {
+    type ValueType = String;
+
+    unsafe fn get_property_value(&self, name: *const c_char, settings: *mut obs_data_t) -> Self::ValueType {
+        CStr::from_ptr(obs_data_get_string(settings, name)).to_string_lossy().into_owned()
+    }
+
+    unsafe fn set_property_value(&self, name: *const c_char, settings: *mut obs_data_t, value: Self::ValueType) {
+        let value = CString::new(value).unwrap_or_default();
+
+        obs_data_set_string(settings, name, value.as_ptr());
+    }
+
+    unsafe fn set_property_default_value(&self, name: *const c_char, settings: *mut obs_data_t, value: Self::ValueType) {
+        let value = CString::new(value).unwrap_or_default();
+
+        obs_data_set_default_string(settings, name, value.as_ptr());
+    }
+}
+
+/// A drop-down list of named integer values.
+#[derive(Clone, Debug)]
+pub struct PropertyDescriptorSpecializationListI32 {
+    /// The names of the items displayed in the UI, paired with the corresponding values.
+    pub items: Vec<(CString, i32)>,
+}
+
+impl PropertyDescriptorSpecializationListI32 {
+    /// Parses a list of items in the form of `<NAME>=<VALUE>;<NAME>=<VALUE>;...`.
+    /// Items without a value are assigned the value of the previous item plus one, starting at 0.
+    pub fn parse_options(options: &str) -> Result<Self, String> {
+        let mut items = Vec::new();
+        let mut next_value = 0;
+
+        for option in options.split(';').map(str::trim).filter(|option| !option.is_empty()) {
+            let (name, value) = if let Some(separator) = option.rfind('=') {
+                let value = option[separator + 1..].trim();
+                let value = value.parse::<i32>()
+                    .map_err(|_| format!("Invalid value `{}` of option `{}`.", value, option))?;
+
+                (option[..separator].trim(), value)
+            } else {
+                (option, next_value)
+            };
+
+            if items.iter().any(|(_, item_value)| *item_value == value) {
+                return Err(format!("Option `{}` has the same value as a previous option.", option));
+            }
+
+            items.push((
+                CString::new(name).map_err(|_| format!("Invalid name of option `{}`.", option))?,
+                value,
+            ));
+            next_value = value.wrapping_add(1);
+        }
+
+        if items.is_empty() {
+            return Err("The list of options is empty.".to_string());
+        }
+
+        Ok(Self { items })
+    }
+
+    pub fn contains(&self, value: i32) -> bool {
+        self.items.iter().any(|(_, item_value)| *item_value == value)
+    }
+}
+
+impl PropertyDescriptorSpecialization for PropertyDescriptorSpecializationListI32 {
+    unsafe fn create_property(
+        &self,
+        name: *const c_char,
+        description: *const c_char,
+        properties: *mut obs_properties_t,
+    ) -> *mut obs_property_t {
+        let property = obs_properties_add_list(
+            properties,
+            name,
+            description,
+            obs_combo_type_OBS_COMBO_TYPE_LIST,
+            obs_combo_format_OBS_COMBO_FORMAT_INT,
+        );
+
+        for (item_name, item_value) in &self.items {
+            obs_property_list_add_int(property, item_name.as_ptr(), *item_value as i64);
+        }
+
+        property
+    }
+}
+
+impl ValuePropertyDescriptorSpecialization for PropertyDescriptorSpecializationListI32 {
+    type ValueType = i32;
+
+    unsafe fn get_property_value(&self, name: *const c_char, settings: *mut obs_data_t) -> Self::ValueType {
+        obs_data_get_int(settings, name) as i32
+    }
+
+    unsafe fn set_property_value(&self, name: *const c_char, settings: *mut obs_data_t, value: Self::ValueType) {
+        obs_data_set_int(settings, name, value as i64);
+    }
+
+    unsafe fn set_property_default_value(&self, name: *const c_char, settings: *mut obs_data_t, value: Self::ValueType) {
+        obs_data_set_default_int(settings, name, value as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(list: &PropertyDescriptorSpecializationListI32) -> Vec<(&str, i32)> {
+        list.items.iter()
+            .map(|(name, value)| (name.to_str().unwrap(), *value))
+            .collect()
+    }
+
+    #[test]
+    fn options_without_values_are_numbered_automatically() {
+        let list = PropertyDescriptorSpecializationListI32::parse_options("Off; Low; High=10; Higher ;").unwrap();
+
+        assert_eq!(items(&list), vec![("Off", 0), ("Low", 1), ("High", 10), ("Higher", 11)]);
+        assert!(list.contains(10));
+        assert!(!list.contains(2));
+    }
+
+    #[test]
+    fn options_with_negative_values_are_parsed() {
+        let list = PropertyDescriptorSpecializationListI32::parse_options("Left=-1;Center;Right").unwrap();
+
+        assert_eq!(items(&list), vec![("Left", -1), ("Center", 0), ("Right", 1)]);
+    }
+
+    #[test]
+    fn duplicate_values_are_rejected() {
+        assert!(PropertyDescriptorSpecializationListI32::parse_options("A=1;B=1").is_err());
+        assert!(PropertyDescriptorSpecializationListI32::parse_options("A=1;B=0;C").is_err());
+    }
+
+    #[test]
+    fn empty_and_invalid_lists_are_rejected() {
+        assert!(PropertyDescriptorSpecializationListI32::parse_options("").is_err());
+        assert!(PropertyDescriptorSpecializationListI32::parse_options(" ; ;").is_err());
+        assert!(PropertyDescriptorSpecializationListI32::parse_options("A=one").is_err());
+    }
+}
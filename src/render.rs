@@ -0,0 +1,292 @@
+use std::cell::Cell;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use obs_wrapper::{
+    obs_sys::*,
+    graphics::*,
+};
+
+thread_local! {
+    /// Set while a source is being rendered into a `RenderTarget`.
+    /// Prevents infinite recursion, when a source is rendered within its own filter.
+    static RENDERING_SOURCE: Cell<bool> = Cell::new(false);
+}
+
+/// An owned `gs_texrender_t`, which can be rendered into and then bound as a texture.
+pub struct RenderTarget {
+    texrender: *mut gs_texrender_t,
+    dimensions: [u32; 2],
+}
+
+impl RenderTarget {
+    pub fn new(_graphics_context: &GraphicsContext) -> Self {
+        Self {
+            texrender: unsafe {
+                gs_texrender_create(gs_color_format_GS_RGBA, gs_zstencil_format_GS_ZS_NONE)
+            },
+            dimensions: [0, 0],
+        }
+    }
+
+    /// The dimensions of the last successful render.
+    pub fn dimensions(&self) -> [u32; 2] {
+        self.dimensions
+    }
+
+    /// Clears the target and calls `render` with an orthographic projection
+    /// matching the specified dimensions.
+    pub fn render(
+        &mut self,
+        dimensions: [u32; 2],
+        _graphics_context: &GraphicsContext,
+        render: impl FnOnce(),
+    ) -> bool {
+        unsafe {
+            gs_texrender_reset(self.texrender);
+
+            if !gs_texrender_begin(self.texrender, dimensions[0], dimensions[1]) {
+                return false;
+            }
+
+            let clear_color: vec4 = std::mem::zeroed();
+
+            gs_clear(GS_CLEAR_COLOR, &clear_color, 0.0, 0);
+            gs_ortho(0.0, dimensions[0] as f32, 0.0, dimensions[1] as f32, -100.0, 100.0);
+            gs_blend_state_push();
+            gs_blend_function(gs_blend_type_GS_BLEND_ONE, gs_blend_type_GS_BLEND_ZERO);
+
+            (render)();
+
+            gs_blend_state_pop();
+            gs_texrender_end(self.texrender);
+        }
+
+        self.dimensions = dimensions;
+
+        true
+    }
+
+    /// Draws the rendered texture into the current render target using the default effect.
+    pub fn draw(&self, _graphics_context: &GraphicsContext) {
+        unsafe {
+            let texture = self.texture();
+            let effect = obs_get_base_effect(obs_base_effect_OBS_EFFECT_DEFAULT);
+            let image = gs_effect_get_param_by_name(effect, b"image\0".as_ptr() as *const c_char);
+
+            gs_effect_set_texture(image, texture);
+
+            while gs_effect_loop(effect, b"Draw\0".as_ptr() as *const c_char) {
+                gs_draw_sprite(texture, 0, self.dimensions[0], self.dimensions[1]);
+            }
+        }
+    }
+
+    /// The rendered texture, owned by the render target.
+    pub fn texture(&self) -> *mut gs_texture_t {
+        unsafe { gs_texrender_get_texture(self.texrender) }
+    }
+
+    pub fn destroy(self, _graphics_context: &GraphicsContext) {
+        unsafe {
+            gs_texrender_destroy(self.texrender);
+        }
+    }
+}
+
+/// Two render targets, of which one holds the output of the previous frame,
+/// while the other one is being rendered into. They are swapped after each frame.
+pub struct FeedbackRenderTargets {
+    render_targets: [RenderTarget; 2],
+    current: usize,
+    previous_valid: bool,
+}
+
+impl FeedbackRenderTargets {
+    pub fn new(graphics_context: &GraphicsContext) -> Self {
+        Self {
+            render_targets: [RenderTarget::new(graphics_context), RenderTarget::new(graphics_context)],
+            current: 0,
+            previous_valid: false,
+        }
+    }
+
+    /// Discards the output of the previous frame, if it does not match the dimensions of the next frame.
+    pub fn prepare(&mut self, dimensions: [u32; 2]) {
+        if self.render_targets[1 - self.current].dimensions() != dimensions {
+            self.previous_valid = false;
+        }
+    }
+
+    /// The output of the previous frame, or null, if there is no valid previous frame.
+    pub fn texture_previous(&self) -> *mut gs_texture_t {
+        if self.previous_valid {
+            self.render_targets[1 - self.current].texture()
+        } else {
+            std::ptr::null_mut()
+        }
+    }
+
+    /// Renders the output of the current frame into a render target using `render` and swaps the render targets,
+    /// so that the output is returned by `texture_previous` afterwards.
+    pub fn render_offscreen(
+        &mut self,
+        dimensions: [u32; 2],
+        graphics_context: &GraphicsContext,
+        render: impl FnOnce(),
+    ) -> bool {
+        if !self.render_targets[self.current].render(dimensions, graphics_context, render) {
+            self.previous_valid = false;
+            return false;
+        }
+
+        self.current = 1 - self.current;
+        self.previous_valid = true;
+
+        true
+    }
+
+    /// Renders the output of the current frame into a render target using `render`,
+    /// then draws it into the current render target.
+    /// Falls back to calling `render` directly, if the render target could not be used.
+    pub fn render(
+        &mut self,
+        dimensions: [u32; 2],
+        graphics_context: &GraphicsContext,
+        mut render: impl FnMut(),
+    ) {
+        if !self.render_offscreen(dimensions, graphics_context, &mut render) {
+            (render)();
+            return;
+        }
+
+        self.render_targets[1 - self.current].draw(graphics_context);
+    }
+
+    pub fn destroy(self, graphics_context: &GraphicsContext) {
+        let [first, second] = self.render_targets;
+
+        first.destroy(graphics_context);
+        second.destroy(graphics_context);
+    }
+}
+
+/// Draws the input of the filter using the effect, with the dimensions of the current render target.
+/// Must be called within `SourceContext::process_filter`, which renders the filter input once,
+/// so that multiple passes can be drawn without rendering the filter input again.
+pub fn draw_filter_input(
+    filter: *mut obs_source_t,
+    effect: *mut gs_effect_t,
+    dimensions: [u32; 2],
+    _graphics_context: &GraphicsContext,
+) {
+    unsafe {
+        obs_source_process_filter_tech_end(
+            filter,
+            effect,
+            dimensions[0],
+            dimensions[1],
+            b"Draw\0".as_ptr() as *const c_char,
+        );
+    }
+}
+
+/// Assigns a texture not managed by `obs_wrapper` to an effect param.
+/// The texture may be null, in which case the param is unbound.
+pub fn set_param_texture(
+    param: &mut GraphicsEffectParamTyped<ShaderParamTypeTexture>,
+    texture: *mut gs_texture_t,
+) {
+    unsafe {
+        gs_effect_set_texture(param.as_ptr(), texture);
+    }
+}
+
+/// A weak reference to a source.
+/// Does not prevent the source from being removed, but keeps it showing, while the source exists.
+pub struct WeakSource {
+    weak: *mut obs_weak_source_t,
+}
+
+impl WeakSource {
+    pub fn by_name(name: &str) -> Option<Self> {
+        if name.is_empty() {
+            return None;
+        }
+
+        let name = CString::new(name).ok()?;
+
+        unsafe {
+            let source = obs_get_source_by_name(name.as_ptr());
+
+            if source.is_null() {
+                return None;
+            }
+
+            let weak = obs_source_get_weak_source(source);
+
+            obs_source_inc_showing(source);
+            obs_source_release(source);
+
+            Some(Self { weak })
+        }
+    }
+
+    /// Calls `f` with a strong reference to the source, if the source still exists.
+    pub fn with_source<R>(&self, f: impl FnOnce(*mut obs_source_t) -> R) -> Option<R> {
+        unsafe {
+            let source = obs_weak_source_get_source(self.weak);
+
+            if source.is_null() {
+                return None;
+            }
+
+            let result = (f)(source);
+
+            obs_source_release(source);
+
+            Some(result)
+        }
+    }
+
+    pub fn exists(&self) -> bool {
+        self.with_source(|_| ()).is_some()
+    }
+
+    /// Renders the source into the render target, sized according to the source.
+    /// Returns `None`, if rendering was skipped to prevent recursion.
+    pub fn render(&self, render_target: &mut RenderTarget, graphics_context: &GraphicsContext) -> Option<bool> {
+        if RENDERING_SOURCE.with(|rendering| rendering.get()) {
+            return None;
+        }
+
+        let rendered = self.with_source(|source| {
+            let dimensions = unsafe {
+                [obs_source_get_width(source), obs_source_get_height(source)]
+            };
+
+            RENDERING_SOURCE.with(|rendering| rendering.set(true));
+
+            let rendered = render_target.render(dimensions, graphics_context, || unsafe {
+                obs_source_video_render(source);
+            });
+
+            RENDERING_SOURCE.with(|rendering| rendering.set(false));
+
+            rendered
+        });
+
+        Some(rendered.unwrap_or(false))
+    }
+}
+
+impl Drop for WeakSource {
+    fn drop(&mut self) {
+        self.with_source(|source| unsafe {
+            obs_source_dec_showing(source);
+        });
+
+        unsafe {
+            obs_weak_source_release(self.weak);
+        }
+    }
+}
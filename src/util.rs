@@ -9,6 +9,7 @@ use obs_wrapper::obs_sys::{
 };
 use std::cmp::Ordering;
 use std::ops::{Deref, DerefMut};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub struct Indexed<T> {
     pub index: usize,
@@ -248,3 +249,24 @@ impl Drop for LogCaptureHandler {
         LOG_HANDLER_LOCK.store(false, atomic::Ordering::SeqCst);
     }
 }
+
+/// The current UTC date as the year, the zero-based month, the day of the month and the
+/// number of seconds since midnight, in the format of Shadertoy's `iDate`.
+pub fn get_utc_date() -> [f32; 4] {
+    let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let days = (since_epoch.as_secs() / 86400) as i64;
+    let seconds = since_epoch.as_secs_f64() - days as f64 * 86400.0;
+
+    // Converts days since 1970-01-01 to a civil date, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 { month_from_march + 2 } else { month_from_march - 10 };
+    let year = year_of_era + era * 400 + if month < 2 { 1 } else { 0 };
+
+    [year as f32, month as f32, day as f32, seconds as f32]
+}
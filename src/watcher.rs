@@ -0,0 +1,132 @@
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant, SystemTime};
+use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Events are collected for this long before being reported, so that
+/// editors which save files in multiple steps only trigger a single reload.
+const WATCHER_DEBOUNCE_DELAY: Duration = Duration::from_millis(200);
+/// How often the modification times are checked when file system
+/// notifications are not available.
+const POLLING_INTERVAL: Duration = Duration::from_millis(500);
+
+enum ShaderWatcherKind {
+    Notify {
+        // Kept around, the events stop being sent when the watcher is dropped.
+        _watcher: RecommendedWatcher,
+        receiver: Receiver<DebouncedEvent>,
+    },
+    Polling {
+        modification_times: Vec<Option<SystemTime>>,
+        polled_at: Instant,
+    },
+}
+
+/// Watches the shader file and the files it includes for changes.
+/// Uses file system notifications, if available, and falls back to polling
+/// the modification times otherwise.
+pub struct ShaderWatcher {
+    paths: Vec<PathBuf>,
+    kind: ShaderWatcherKind,
+}
+
+impl ShaderWatcher {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        let paths = Self::canonicalize_paths(paths);
+        let kind = Self::create_notify_watcher(&paths)
+            .unwrap_or_else(|err| {
+                println!("Could not watch the shader for changes, falling back to polling: {}", err);
+
+                ShaderWatcherKind::Polling {
+                    modification_times: Self::get_modification_times(&paths),
+                    polled_at: Instant::now(),
+                }
+            });
+
+        Self { paths, kind }
+    }
+
+    fn canonicalize_paths(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        paths.into_iter()
+            .map(|path| path.canonicalize().unwrap_or(path))
+            .collect()
+    }
+
+    fn create_notify_watcher(paths: &[PathBuf]) -> notify::Result<ShaderWatcherKind> {
+        let (sender, receiver) = channel();
+        let mut watcher = watcher(sender, WATCHER_DEBOUNCE_DELAY)?;
+        let mut directories = paths.iter()
+            .filter_map(|path| path.parent())
+            .collect::<Vec<_>>();
+
+        directories.sort();
+        directories.dedup();
+
+        // Watch the parent directories instead of the files themselves, so that
+        // atomic saves (writing a temporary file and renaming it) are detected.
+        for directory in directories {
+            watcher.watch(directory, RecursiveMode::NonRecursive)?;
+        }
+
+        Ok(ShaderWatcherKind::Notify {
+            _watcher: watcher,
+            receiver,
+        })
+    }
+
+    fn get_modification_times(paths: &[PathBuf]) -> Vec<Option<SystemTime>> {
+        paths.iter()
+            .map(|path| {
+                std::fs::metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Returns `true`, if this watcher observes exactly the specified files.
+    pub fn is_watching(&self, paths: &[PathBuf]) -> bool {
+        Self::canonicalize_paths(paths.to_vec()) == self.paths
+    }
+
+    /// Returns `true`, if any of the watched files changed since the last call.
+    pub fn poll_changed(&mut self) -> bool {
+        let paths = &self.paths;
+
+        match &mut self.kind {
+            ShaderWatcherKind::Notify { receiver, .. } => {
+                let mut changed = false;
+
+                for event in receiver.try_iter() {
+                    match event {
+                        DebouncedEvent::Create(path)
+                            | DebouncedEvent::Write(path)
+                            | DebouncedEvent::Remove(path)
+                            | DebouncedEvent::Rename(_, path) => {
+                            changed |= paths.contains(&path);
+                        },
+                        DebouncedEvent::Rescan => {
+                            changed = true;
+                        },
+                        _ => (),
+                    }
+                }
+
+                changed
+            },
+            ShaderWatcherKind::Polling { modification_times, polled_at } => {
+                if polled_at.elapsed() < POLLING_INTERVAL {
+                    return false;
+                }
+
+                let modification_times_current = Self::get_modification_times(paths);
+                let changed = *modification_times != modification_times_current;
+
+                *modification_times = modification_times_current;
+                *polled_at = Instant::now();
+
+                changed
+            },
+        }
+    }
+}